@@ -4,7 +4,7 @@ use std::{any::Any, error::Error, hint::black_box, str::FromStr};
 
 use criterion::{Criterion, Throughput, criterion_group, criterion_main};
 
-use scanf::sscanf;
+use scanf::{sscan, sscanf};
 
 const U16_NUMBERS_SEPARATED_BY_COMMAS: [&str; 5] = [
     "1,2,3,4,5,6,7,8,9,0",
@@ -63,6 +63,13 @@ where
     )
 }
 
+fn sscan_10_same_elements_of<T: FromStr + Any>(input: &str) -> (T, T, T, T, T, T, T, T, T, T)
+where
+    <T as FromStr>::Err: Error + Send + Sync,
+{
+    sscan!(input, "{},{},{},{},{},{},{},{},{},{}").unwrap()
+}
+
 fn sscanf_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("split-benchmark");
     let input = black_box("Candy 2.75");
@@ -111,6 +118,11 @@ fn sscanf_benchmark(c: &mut Criterion) {
             input,
             |b, input| b.iter(|| sscanf_10_same_elements_of::<String>(input)),
         );
+        group.bench_with_input(
+            format!("Sscan u16 as u16 separated by commas {}", i),
+            input,
+            |b, input| b.iter(|| sscan_10_same_elements_of::<u16>(input)),
+        );
     }
     group.finish();
 
@@ -1,245 +1,1844 @@
 //! Code generation for scanf macros.
 
+use crate::constants::MAX_BACKTRACK_SCAN;
+use crate::parsing::ExplicitArgs;
 use crate::tokenization::tokenize_format_string;
-use crate::types::{FormatToken, Placeholder};
+use crate::types::{CharClass, FormatToken, Placeholder, Radix};
+use crate::validation::is_valid_identifier;
 use proc_macro::TokenStream;
-use proc_macro2::Span;
-use quote::quote;
-use syn::{Expr, Ident, LitStr, spanned::Spanned};
+use proc_macro2::{Literal, Span};
+use quote::{ToTokens, format_ident, quote};
+use syn::{Expr, Ident, LitByteStr, LitStr, Type, spanned::Spanned};
+
+/// Kind of input the generated code scans: `remaining` is a `&str` or a `&[u8]`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Str,
+    Bytes,
+}
+
+impl Input {
+    /// Literal for format text, typed like the input (`"..."` or `b"..."`).
+    fn literal(self, text: &str) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => LitStr::new(text, Span::call_site()).to_token_stream(),
+            Input::Bytes => LitByteStr::new(text.as_bytes(), Span::call_site()).to_token_stream(),
+        }
+    }
+
+    /// `Option<usize>` expression: offset of `needle` in `remaining`, ignoring ASCII case
+    /// if asked.
+    fn find(
+        self,
+        needle: &proc_macro2::TokenStream,
+        ignore_case: bool,
+    ) -> proc_macro2::TokenStream {
+        self.find_in(&quote! { remaining }, needle, ignore_case)
+    }
+
+    /// `Option<usize>` expression: offset of `needle` in `haystack`, ignoring ASCII case if
+    /// asked. Case never changes byte lengths, so the match is `needle.len()` long.
+    fn find_in(
+        self,
+        haystack: &proc_macro2::TokenStream,
+        needle: &proc_macro2::TokenStream,
+        ignore_case: bool,
+    ) -> proc_macro2::TokenStream {
+        match (self, ignore_case) {
+            (Input::Str, false) => quote! { #haystack.find(#needle) },
+            (Input::Bytes, false) => quote! {
+                #haystack.windows(#needle.len()).position(|window| window == #needle)
+            },
+            (Input::Str, true) => quote! {
+                #haystack
+                    .as_bytes()
+                    .windows(#needle.len())
+                    .position(|window| window.eq_ignore_ascii_case(#needle.as_bytes()))
+            },
+            (Input::Bytes, true) => quote! {
+                #haystack
+                    .windows(#needle.len())
+                    .position(|window| window.eq_ignore_ascii_case(#needle))
+            },
+        }
+    }
+
+    /// `Option<usize>` expression: offset of the last `needle` in `haystack`, ignoring ASCII
+    /// case if asked.
+    fn rfind_in(
+        self,
+        haystack: &proc_macro2::TokenStream,
+        needle: &proc_macro2::TokenStream,
+        ignore_case: bool,
+    ) -> proc_macro2::TokenStream {
+        match (self, ignore_case) {
+            (Input::Str, false) => quote! { #haystack.rfind(#needle) },
+            (Input::Bytes, false) => quote! {
+                #haystack.windows(#needle.len()).rposition(|window| window == #needle)
+            },
+            (Input::Str, true) => quote! {
+                #haystack
+                    .as_bytes()
+                    .windows(#needle.len())
+                    .rposition(|window| window.eq_ignore_ascii_case(#needle.as_bytes()))
+            },
+            (Input::Bytes, true) => quote! {
+                #haystack
+                    .windows(#needle.len())
+                    .rposition(|window| window.eq_ignore_ascii_case(#needle))
+            },
+        }
+    }
+
+    /// `Option<usize>` expression: offset just after `width` chars (or bytes).
+    fn width_end(self, width: usize) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! {
+                remaining
+                    .char_indices()
+                    .map(|(index, _)| index)
+                    .chain(std::iter::once(remaining.len()))
+                    .nth(#width)
+            },
+            Input::Bytes => quote! { (remaining.len() >= #width).then_some(#width) },
+        }
+    }
+
+    /// `usize` expression: offset of the first char (or byte) for which `stop` matches,
+    /// `remaining.len()` if none. `stop` is a `matches!` pattern body.
+    fn span_end(self, stop: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! {
+                remaining.find(|c: char| #stop).unwrap_or(remaining.len())
+            },
+            Input::Bytes => quote! {
+                remaining.iter().position(|&c| #stop).unwrap_or(remaining.len())
+            },
+        }
+    }
+
+    /// Statement skipping leading whitespace of `remaining`.
+    fn skip_whitespace(self) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! { remaining = remaining.trim_start(); },
+            Input::Bytes => quote! { remaining = remaining.trim_ascii_start(); },
+        }
+    }
+
+    /// Statement dropping trailing whitespace of `remaining`.
+    fn trim_end(self) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! { remaining = remaining.trim_end(); },
+            Input::Bytes => quote! { remaining = remaining.trim_ascii_end(); },
+        }
+    }
+
+    /// `matches!`-style condition: `c` is whitespace.
+    fn is_whitespace(self) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! { c.is_whitespace() },
+            Input::Bytes => quote! { c.is_ascii_whitespace() },
+        }
+    }
+
+    /// Unit counted by fixed widths, for error messages.
+    fn width_unit(self) -> &'static str {
+        match self {
+            Input::Str => "characters",
+            Input::Bytes => "bytes",
+        }
+    }
+
+    /// Expression printable with `{:?}` in error messages.
+    fn show(self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => value,
+            Input::Bytes => quote! { String::from_utf8_lossy(#value) },
+        }
+    }
+
+    /// Empty input value, assigned once a final placeholder consumed everything.
+    fn empty(self) -> proc_macro2::TokenStream {
+        match self {
+            Input::Str => quote! { "" },
+            Input::Bytes => quote! { &[] },
+        }
+    }
+}
+
+/// How placeholder values are stored.
+pub enum Captures<'a> {
+    /// `sscanf!`/`scanf!`: `{name}` assigns to the named argument or else the variable,
+    /// `{}` to the next `&mut` argument.
+    Assign(&'a ExplicitArgs),
+    /// Value-producing macros: every placeholder fills its own [`Slot`], in format order.
+    Collect,
+}
+
+/// Opt-in matching rules.
+#[derive(Clone, Copy, Default)]
+pub struct MatchOptions {
+    /// C `scanf` whitespace: whitespace in literal text matches any run of whitespace
+    /// (`\s*`) and fields skip leading whitespace, except character sets as with `%[`.
+    pub c_whitespace: bool,
+    /// Literal text matches ignoring ASCII case; set by a `(?i)` format prefix.
+    pub ignore_case: bool,
+    /// When parsing fails, fields ended by a separator retry at its later occurrences;
+    /// set by a `(?b)` format prefix. Bounded by [`MAX_BACKTRACK_SCAN`] re-scanned bytes.
+    pub backtrack: bool,
+    /// Trailing input is an error. Checked inside the generated code, so backtracking
+    /// also retries on it.
+    pub whole_input: bool,
+}
+
+/// Temporary `Option` holding one captured value in [`Captures::Collect`] mode.
+pub struct Slot {
+    /// Mixed-site identifier, invisible to user code.
+    pub ident: Ident,
+    /// Placeholder name, `None` for `{}`.
+    pub name: Option<Box<str>>,
+    /// Inline type annotation (`{x:i32}`).
+    pub ty: Option<Type>,
+    /// Inside an optional section: the captured value is the `Option` itself.
+    pub optional: bool,
+}
+
+/// Destination of a parsed placeholder value.
+enum Target<'a> {
+    /// `{name}`: assign to the variable in scope, or to a place like `point.x` or `arr[0]`.
+    Variable(&'a str),
+    /// `{}`: assign through the `&mut` argument (1-based placeholder number).
+    Argument(&'a Expr, usize),
+    /// `{N}`: assign through the `&mut` argument at 0-based position `N`.
+    Positional(&'a Expr, usize),
+    /// `{name}` with a `name = &mut place` argument: assign through that argument.
+    NamedArgument(&'a Expr, &'a str),
+    /// Argument already captured by an earlier placeholder (description, argument): the
+    /// parsed value must equal the stored one.
+    Equal(String, &'a Expr),
+    /// Stored into an `Option` (description, identifier): a collected [`Slot`], or a value
+    /// staged inside an optional section.
+    Slot(String, Ident),
+    /// `{_}`: matched, then dropped (1-based discard number).
+    Discard(usize),
+    /// Element of a repeated capture, pushed to a vector (description, vector identifier).
+    Element(String, Ident),
+}
+
+impl Target<'_> {
+    /// Human-readable name used in runtime error messages.
+    fn description(&self) -> String {
+        match self {
+            Target::Variable(name) => format!("variable '{}'", name),
+            Target::Argument(_, placeholder_num) => {
+                format!("anonymous placeholder #{}", placeholder_num)
+            }
+            Target::Positional(_, index) => format!("positional placeholder {{{}}}", index),
+            Target::NamedArgument(_, name) => format!("argument '{}'", name),
+            Target::Slot(description, _) | Target::Equal(description, _) => description.clone(),
+            Target::Discard(discard_num) => format!("discarded placeholder #{}", discard_num),
+            Target::Element(description, _) => format!("element of {}", description),
+        }
+    }
+
+    /// Statement storing `parsed` into the target.
+    fn assign(&self) -> proc_macro2::TokenStream {
+        match self {
+            Target::Variable(name) => {
+                let place: Expr =
+                    syn::parse_str(name).expect("field path checked by the tokenizer");
+                quote! { #place = parsed; }
+            }
+            Target::Argument(arg_expr, _)
+            | Target::Positional(arg_expr, _)
+            | Target::NamedArgument(arg_expr, _) => {
+                quote! { *#arg_expr = parsed; }
+            }
+            Target::Equal(description, arg_expr) => {
+                let message = format!(
+                    "{} does not match the earlier capture of its argument",
+                    description
+                );
+                let same = Ident::new("same", Span::mixed_site());
+                quote! {{
+                    fn #same<T: PartialEq>(stored: &T, parsed: &T) -> bool {
+                        stored == parsed
+                    }
+                    if !#same(&*#arg_expr, &parsed) {
+                        result = result.and(Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            #message
+                        )));
+                    }
+                }}
+            }
+            Target::Slot(_, slot) => quote! { #slot = Some(parsed); },
+            Target::Discard(_) => quote! { let _ = parsed; },
+            Target::Element(_, values) => quote! { #values.push(parsed); },
+        }
+    }
+}
+
+/// Placeholder resolved to its target, with its inline type, radix and repeat separator.
+struct Field<'a> {
+    target: Target<'a>,
+    ty: Option<Type>,
+    radix: Option<Radix>,
+    repeat: Option<&'a str>,
+    /// `{name:last}`: ends at the last occurrence of its separator.
+    last: bool,
+    /// `(?i)`: the repeat separator matches ignoring ASCII case.
+    ignore_case: bool,
+}
+
+/// `T` of a `Vec<T>` type annotation.
+fn vec_element(ty: &Type) -> Option<&Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    match (segment.ident == "Vec", arguments.args.first()) {
+        (true, Some(syn::GenericArgument::Type(element))) if arguments.args.len() == 1 => {
+            Some(element)
+        }
+        _ => None,
+    }
+}
+
+/// `&str` / `&[u8]` annotations borrow the captured slice instead of parsing it.
+fn borrowed_slice(ty: Option<&Type>) -> Option<Input> {
+    let Some(Type::Reference(reference)) = ty else {
+        return None;
+    };
+    if reference.mutability.is_some() {
+        return None;
+    }
+    match &*reference.elem {
+        Type::Path(path) if path.path.is_ident("str") => Some(Input::Str),
+        Type::Slice(slice) => match &*slice.elem {
+            Type::Path(path) if path.path.is_ident("u8") => Some(Input::Bytes),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Output of [`generate_parsing_code`].
+struct ParsingCode {
+    generated: Vec<proc_macro2::TokenStream>,
+    /// Explicit arguments bound to at least one placeholder.
+    used_args: Vec<bool>,
+    slots: Vec<Slot>,
+}
+
+/// State shared by the top-level sequence and the optional sections of one format.
+struct ParsingState<'a, 'b> {
+    captures: &'b Captures<'a>,
+    input: Input,
+    options: MatchOptions,
+    format_lit: &'b LitStr,
+    slots: Vec<Slot>,
+    anon_index: usize,
+    discard_index: usize,
+    /// Optional-section depth of each explicit argument's first placeholder, `None` if unused.
+    arg_depths: Vec<Option<usize>>,
+    /// `Captures::Assign` targets inside optional sections, with their staging `Option`
+    /// and inline type. Stored into the target once the outermost section matches.
+    staged: Vec<(Ident, Target<'a>, Option<Type>)>,
+    /// Optional sections currently open.
+    depth: usize,
+    /// Backtracking choice points generated so far.
+    choice_count: usize,
+}
+
+impl ParsingState<'_, '_> {
+    /// Index of a new backtracking choice point, `None` without backtracking.
+    ///
+    /// Optional sections swallow their own errors, so retrying inside them cannot fix a
+    /// parse; their fields get no choice points.
+    fn next_choice(&mut self) -> Option<usize> {
+        if !self.options.backtrack || self.depth > 0 {
+            return None;
+        }
+        self.choice_count += 1;
+        Some(self.choice_count - 1)
+    }
+}
+
+/// Mixed-site identifiers of the backtracking loop, shared by its choice points.
+struct BacktrackIdents {
+    /// `[usize; N]`: separator occurrence picked at each choice point.
+    choices: Ident,
+    /// `[bool; N]`: whether each choice point has a later occurrence to retry.
+    more: Ident,
+    /// Choice points reached before the first error.
+    reached: Ident,
+    /// Input bytes charged to the retries so far.
+    scanned: Ident,
+    start: Ident,
+    /// Whether failed attempts format their error messages; see [`generate_error`].
+    detailed: Ident,
+    gave_up: Ident,
+}
+
+impl BacktrackIdents {
+    fn new() -> Self {
+        let ident = |name| Ident::new(name, Span::mixed_site());
+        BacktrackIdents {
+            choices: ident("choices"),
+            more: ident("more"),
+            reached: ident("reached"),
+            scanned: ident("scanned"),
+            start: ident("start"),
+            detailed: ident("detailed"),
+            gave_up: ident("gave_up"),
+        }
+    }
+}
+
+/// Expression building an `io::Error` of `kind` with a `format!(message)` message.
+///
+/// Messages show the remaining input, so discarded backtracking attempts skip them and get a
+/// bare `kind`; only the reported attempt formats its message.
+fn generate_error(
+    kind: proc_macro2::TokenStream,
+    message: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let detailed = Ident::new("detailed", Span::mixed_site());
+    quote! {
+        if #detailed {
+            std::io::Error::new(std::io::ErrorKind::#kind, format!(#message))
+        } else {
+            std::io::Error::from(std::io::ErrorKind::#kind)
+        }
+    }
+}
 
 /// Generate parsing code from tokens.
 ///
-/// Returns `(code, anon_count)` or error for consecutive placeholders / missing args.
-fn generate_parsing_code(
-    tokens: &[FormatToken],
-    explicit_args: &[&Expr],
+/// Errors on consecutive placeholders, missing args or invalid inline types.
+fn generate_parsing_code<'a>(
+    tokens: &'a [FormatToken],
+    captures: &Captures<'a>,
+    input: Input,
+    options: MatchOptions,
     format_lit: &LitStr,
-) -> Result<(Vec<proc_macro2::TokenStream>, usize), TokenStream> {
+) -> Result<ParsingCode, TokenStream> {
+    let mut state = ParsingState {
+        captures,
+        input,
+        options,
+        format_lit,
+        slots: Vec::new(),
+        anon_index: 0,
+        discard_index: 0,
+        arg_depths: match captures {
+            Captures::Assign(explicit_args) => {
+                vec![None; explicit_args.positional.len() + explicit_args.named.len()]
+            }
+            Captures::Collect => Vec::new(),
+        },
+        staged: Vec::new(),
+        depth: 0,
+        choice_count: 0,
+    };
+    let mut generated = generate_sequence(tokens, &mut state, true)?;
+    if options.whole_input {
+        generated.push(generate_end_of_input_check());
+    }
+    if state.choice_count > 0 {
+        generated = vec![generate_backtracking_loop(&generated, state.choice_count)];
+    } else {
+        let detailed = Ident::new("detailed", Span::mixed_site());
+        generated.insert(
+            0,
+            quote! { #[allow(unused_variables)] let #detailed = true; },
+        );
+    }
+
+    Ok(ParsingCode {
+        generated,
+        used_args: state.arg_depths.iter().map(Option::is_some).collect(),
+        slots: state.slots,
+    })
+}
+
+/// Wrap the parsing code in a loop retrying failed parses with other separator occurrences.
+///
+/// Each retry moves the last choice point reached before the first error, that has a later
+/// occurrence, to that occurrence, and resets the choice points after it (depth-first
+/// search). A retry re-scans the input, so each one is charged its length plus one, and
+/// retries stop once [`MAX_BACKTRACK_SCAN`] bytes are charged. Failed attempts skip their
+/// error messages; the last one is re-run with them to report its error.
+fn generate_backtracking_loop(
+    generated: &[proc_macro2::TokenStream],
+    choice_count: usize,
+) -> proc_macro2::TokenStream {
+    let BacktrackIdents {
+        choices,
+        more,
+        reached,
+        scanned,
+        start,
+        detailed,
+        gave_up,
+    } = BacktrackIdents::new();
+
+    quote! {
+        let #start = remaining;
+        let mut #choices = [0usize; #choice_count];
+        let mut #scanned: usize = 0;
+        let mut #detailed = false;
+        let mut #gave_up = false;
+        loop {
+            let mut #reached: usize = 0;
+            let mut #more = [false; #choice_count];
+            #(#generated)*
+            if result.is_ok() || #detailed {
+                break;
+            }
+            match (0..#reached).rev().find(|&choice| #more[choice]) {
+                Some(choice) if #scanned + #start.len() < #MAX_BACKTRACK_SCAN => {
+                    #scanned += #start.len() + 1;
+                    #choices[choice] += 1;
+                    #choices[choice + 1..].fill(0);
+                }
+                next => {
+                    // Re-run the last attempt with its error messages
+                    #gave_up = next.is_some();
+                    #detailed = true;
+                }
+            }
+            result = Ok(());
+            remaining = #start;
+        }
+        if #gave_up {
+            result = result.map_err(|error| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Gave up backtracking after re-scanning {} bytes of input: {}",
+                        #scanned,
+                        error
+                    )
+                )
+            });
+        }
+    }
+}
+
+/// Generate code for a sequence of tokens (the format, or an optional section's content).
+///
+/// A trailing placeholder without delimiter consumes the rest of the input, which is only
+/// allowed when the sequence `ends_format`.
+fn generate_sequence<'a>(
+    tokens: &'a [FormatToken],
+    state: &mut ParsingState<'a, '_>,
+    ends_format: bool,
+) -> Result<Vec<proc_macro2::TokenStream>, TokenStream> {
+    let input = state.input;
+    let ignore_case = state.options.ignore_case;
+    let format_lit = state.format_lit;
     let mut generated = Vec::with_capacity(tokens.len());
-    let mut pending_placeholder: Option<Placeholder> = None;
-    let mut anon_index: usize = 0;
+    let mut pending_field: Option<Field> = None;
 
-    for token in tokens {
+    for (token_index, token) in tokens.iter().enumerate() {
+        let is_final = ends_format && token_index + 1 == tokens.len();
         match token {
             FormatToken::Placeholder(ph) => {
-                if pending_placeholder.is_some() {
+                if pending_field.is_some() {
                     return Err(syn::Error::new(
                         format_lit.span(),
                         "Consecutive placeholders without separator are ambiguous and not supported. \
-                         Add text between placeholders to separate them. Example: '{}:{}' instead of '{}{}'",
+                         Add text between placeholders or give the first one a fixed width \
+                         or character set. Example: '{}:{}', '{:4}{}' or '{:[0-9]}{}' instead of '{}{}'",
                     )
                     .to_compile_error()
                     .into());
                 }
-                pending_placeholder = Some(ph.clone());
+
+                let spec = ph.spec();
+                let field = resolve_field(ph, state, is_final)?;
+                if state.options.c_whitespace && spec.class.is_none() {
+                    generated.push(input.skip_whitespace());
+                }
+                match (spec.width, &spec.class, &spec.regex) {
+                    _ if spec.quoted => {
+                        generated.push(generate_quoted_placeholder(&field, input));
+                    }
+                    (Some(width), _, _) => {
+                        generated.push(generate_fixed_width_placeholder(&field, input, width));
+                    }
+                    (None, Some(class), _) => {
+                        let pattern = generate_class_pattern(class, input, format_lit)?;
+                        generated.push(generate_char_class_placeholder(
+                            &field, input, class, &pattern,
+                        ));
+                    }
+                    (None, None, Some(regex)) => {
+                        let anchored = anchored_regex(regex, input, format_lit)?;
+                        generated.push(generate_regex_placeholder(&field, input, regex, &anchored));
+                    }
+                    (None, None, None) => pending_field = Some(field),
+                }
             }
-            FormatToken::Text(text) => {
-                let lit_text = LitStr::new(text, Span::call_site());
+            FormatToken::Text(text) if state.options.c_whitespace => {
+                let runs = whitespace_runs(text);
+                let next = runs.get(1).map(|&(_, run)| input.literal(run));
+                let mut runs = runs.into_iter().peekable();
 
-                if let Some(ph) = pending_placeholder.take() {
-                    match ph {
-                        Placeholder::Named(name) => {
-                            generated
-                                .push(generate_named_placeholder_with_separator(&name, &lit_text));
+                if let Some(field) = pending_field.take() {
+                    match runs.peek() {
+                        Some(&(true, _)) if field.last => {
+                            return Err(make_last_without_text_error(format_lit));
+                        }
+                        Some(&(true, _)) => {
+                            generated.push(generate_whitespace_delimited_placeholder(
+                                &field,
+                                input,
+                                next.as_ref(),
+                                ignore_case,
+                            ));
                         }
-                        Placeholder::Anonymous => {
-                            if anon_index >= explicit_args.len() {
-                                return Err(make_missing_argument_error(
-                                    anon_index + 1,
-                                    false,
-                                    format_lit,
+                        _ => {
+                            let (_, separator) = runs.next().expect("text is not empty");
+                            if field.last {
+                                let bound = runs
+                                    .clone()
+                                    .find(|&(is_whitespace, _)| !is_whitespace)
+                                    .map(|(_, run)| run)
+                                    .or_else(|| next_literal(&tokens[token_index + 1..], true));
+                                generated.push(generate_last_separator_placeholder(
+                                    &field,
+                                    input,
+                                    separator,
+                                    bound,
+                                    ignore_case,
+                                ));
+                            } else {
+                                let choice = state.next_choice();
+                                generated.push(generate_placeholder_with_separator(
+                                    &field,
+                                    input,
+                                    separator,
+                                    ignore_case,
+                                    choice,
                                 ));
                             }
-                            let arg_expr = explicit_args[anon_index];
-                            anon_index += 1;
-                            generated.push(generate_anonymous_placeholder_with_separator(
-                                arg_expr, anon_index, &lit_text,
-                            ));
                         }
                     }
+                }
+
+                for (is_whitespace, run) in runs {
+                    generated.push(if is_whitespace {
+                        input.skip_whitespace()
+                    } else {
+                        generate_fixed_text_match(input, &input.literal(run), ignore_case)
+                    });
+                }
+            }
+            FormatToken::Text(text) => {
+                let lit_text = input.literal(text);
+
+                if let Some(field) = pending_field.take() {
+                    if field.last {
+                        let bound = next_literal(&tokens[token_index + 1..], false);
+                        generated.push(generate_last_separator_placeholder(
+                            &field,
+                            input,
+                            text,
+                            bound,
+                            ignore_case,
+                        ));
+                    } else {
+                        let choice = state.next_choice();
+                        generated.push(generate_placeholder_with_separator(
+                            &field,
+                            input,
+                            text,
+                            ignore_case,
+                            choice,
+                        ));
+                    }
                 } else {
-                    generated.push(generate_fixed_text_match(&lit_text));
+                    generated.push(generate_fixed_text_match(input, &lit_text, ignore_case));
+                }
+            }
+            FormatToken::Alternation(ph) => {
+                let alternatives = ph.spec().alternatives.as_deref().unwrap_or_default();
+                let field = resolve_field(ph, state, is_final)?;
+                let previous = pending_field.take();
+                if previous.as_ref().is_some_and(|previous| previous.last) {
+                    return Err(make_last_without_text_error(format_lit));
+                }
+                if previous.is_none() && state.options.c_whitespace {
+                    generated.push(input.skip_whitespace());
+                }
+                generated.push(generate_alternation(
+                    &field,
+                    previous.as_ref(),
+                    input,
+                    alternatives,
+                    ignore_case,
+                ));
+            }
+            FormatToken::Optional(section) => {
+                if pending_field.is_some() {
+                    return Err(syn::Error::new(
+                        format_lit.span(),
+                        "A placeholder directly before an optional section needs a fixed width, \
+                         character set or regex, as the section may be absent. \
+                         Example: '{:[^ ]}[[ {}]]?' instead of '{}[[ {}]]?'",
+                    )
+                    .to_compile_error()
+                    .into());
                 }
+                generated.push(generate_optional_section(section, state, is_final)?);
             }
         }
     }
 
-    if let Some(ph) = pending_placeholder {
-        match ph {
-            Placeholder::Named(name) => {
-                generated.push(generate_final_named_placeholder(&name));
+    if let Some(field) = pending_field {
+        if !ends_format {
+            return Err(syn::Error::new(
+                format_lit.span(),
+                "A placeholder at the end of an optional section needs a fixed width, \
+                 character set or regex, unless the section ends the format. \
+                 Example: '[[{:[0-9]}]]?' or '[[{},]]?' instead of '[[{}]]?'",
+            )
+            .to_compile_error()
+            .into());
+        }
+        if field.last {
+            return Err(make_last_without_text_error(format_lit));
+        }
+        if state.options.c_whitespace {
+            generated.push(input.trim_end());
+        }
+        generated.push(generate_final_placeholder(&field, input));
+    }
+
+    Ok(generated)
+}
+
+/// Resolve a placeholder to its target (variable, argument, slot or discard) and check its
+/// inline type. Inside optional sections, assignments are staged.
+fn resolve_field<'a>(
+    ph: &'a Placeholder,
+    state: &mut ParsingState<'a, '_>,
+    is_final: bool,
+) -> Result<Field<'a>, TokenStream> {
+    let format_lit = state.format_lit;
+    let spec = ph.spec();
+    let ty = match &spec.ty {
+        Some(ty) => Some(syn::parse_str::<Type>(ty).map_err(|error| {
+            TokenStream::from(
+                syn::Error::new(
+                    format_lit.span(),
+                    format!("Invalid type '{}' in placeholder: {}", ty, error),
+                )
+                .to_compile_error(),
+            )
+        })?),
+        None => None,
+    };
+
+    let target = match (state.captures, ph) {
+        (_, Placeholder::Discard(_)) => {
+            state.discard_index += 1;
+            Target::Discard(state.discard_index)
+        }
+        (Captures::Assign(explicit_args), Placeholder::Named(name, _)) => {
+            match explicit_args
+                .named
+                .iter()
+                .position(|(arg, _)| *arg == **name)
+            {
+                Some(position) => argument_target(
+                    state,
+                    explicit_args.positional.len() + position,
+                    Target::NamedArgument(&explicit_args.named[position].1, name),
+                )?,
+                None => Target::Variable(name),
             }
-            Placeholder::Anonymous => {
-                if anon_index >= explicit_args.len() {
-                    return Err(make_missing_argument_error(
-                        anon_index + 1,
-                        true,
-                        format_lit,
-                    ));
-                }
-                let arg_expr = explicit_args[anon_index];
-                anon_index += 1;
-                generated.push(generate_final_anonymous_placeholder(arg_expr, anon_index));
+        }
+        (Captures::Assign(explicit_args), Placeholder::Anonymous(_)) => {
+            if state.anon_index >= explicit_args.positional.len() {
+                return Err(make_missing_argument_error(
+                    state.anon_index + 1,
+                    is_final,
+                    format_lit,
+                ));
             }
+            let arg_expr = &explicit_args.positional[state.anon_index];
+            state.anon_index += 1;
+            argument_target(
+                state,
+                state.anon_index - 1,
+                Target::Argument(arg_expr, state.anon_index),
+            )?
+        }
+        (Captures::Assign(explicit_args), &Placeholder::Indexed(index, _)) => {
+            let Some(arg_expr) = explicit_args.positional.get(index) else {
+                return Err(syn::Error::new(
+                    format_lit.span(),
+                    format!(
+                        "Positional placeholder '{{{}}}' refers to argument {}, but only {} \
+                         argument(s) were provided (positions count from 0)",
+                        index,
+                        index,
+                        explicit_args.positional.len()
+                    ),
+                )
+                .to_compile_error()
+                .into());
+            };
+            argument_target(state, index, Target::Positional(arg_expr, index))?
+        }
+        (Captures::Collect, Placeholder::Indexed(index, _)) => {
+            return Err(syn::Error::new(
+                format_lit.span(),
+                format!(
+                    "Positional placeholder '{{{}}}' refers to an explicit argument, \
+                     which this macro does not take. Use '{{}}' or a named placeholder",
+                    index
+                ),
+            )
+            .to_compile_error()
+            .into());
+        }
+        (Captures::Collect, Placeholder::Named(name, _)) if !is_valid_identifier(name) => {
+            return Err(syn::Error::new(
+                format_lit.span(),
+                format!(
+                    "Placeholder '{{{}}}' assigns to an existing place, but this macro \
+                     declares new values; use a plain name",
+                    name
+                ),
+            )
+            .to_compile_error()
+            .into());
+        }
+        (Captures::Collect, _) => {
+            let (description, name) = match ph {
+                Placeholder::Named(name, _) => (format!("variable '{}'", name), Some(name.clone())),
+                Placeholder::Anonymous(_) => {
+                    state.anon_index += 1;
+                    (format!("anonymous placeholder #{}", state.anon_index), None)
+                }
+                Placeholder::Indexed(..) | Placeholder::Discard(_) => {
+                    unreachable!("handled above")
+                }
+            };
+            let ident = format_ident!("slot_{}", state.slots.len(), span = Span::mixed_site());
+            state.slots.push(Slot {
+                ident: ident.clone(),
+                name,
+                ty: ty.clone(),
+                optional: state.depth > 0,
+            });
+            Target::Slot(description, ident)
+        }
+    };
+
+    // Inside optional sections, assignments wait until the section matches
+    let target = match target {
+        Target::Variable(_)
+        | Target::Argument(..)
+        | Target::Positional(..)
+        | Target::NamedArgument(..)
+            if state.depth > 0 =>
+        {
+            let ident = format_ident!("staged_{}", state.staged.len(), span = Span::mixed_site());
+            let description = target.description();
+            state.staged.push((ident.clone(), target, ty.clone()));
+            Target::Slot(description, ident)
         }
+        target => target,
+    };
+
+    let element_ty = match (&spec.repeat, &ty) {
+        (Some(_), Some(ty)) => Some(vec_element(ty).ok_or_else(|| {
+            TokenStream::from(
+                syn::Error::new(
+                    format_lit.span(),
+                    "A repeated placeholder captures a 'Vec<T>'; \
+                     annotate it as '{name:Vec<T>;sep=\",\"}' or leave the type out",
+                )
+                .to_compile_error(),
+            )
+        })?),
+        _ => ty.as_ref(),
+    };
+    if spec.radix.is_some() && borrowed_slice(element_ty).is_some() {
+        return Err(syn::Error::new(
+            format_lit.span(),
+            "A radix parses integers; it cannot be combined with a borrowed \
+             '&str' or '&[u8]' type",
+        )
+        .to_compile_error()
+        .into());
+    }
+
+    if spec.quoted && borrowed_slice(element_ty).is_some() {
+        return Err(syn::Error::new(
+            format_lit.span(),
+            "A quoted field is unescaped into a new string, so it cannot be borrowed; \
+             capture it as 'String' or another 'FromStr' type",
+        )
+        .to_compile_error()
+        .into());
     }
 
-    Ok((generated, anon_index))
+    Ok(Field {
+        target,
+        ty,
+        radix: spec.radix,
+        repeat: spec.repeat.as_deref(),
+        last: spec.last,
+        ignore_case: state.options.ignore_case,
+    })
 }
 
-/// Generate code for named placeholder with separator.
-fn generate_named_placeholder_with_separator(
-    name: &str,
-    separator: &LitStr,
-) -> proc_macro2::TokenStream {
-    let ident = Ident::new(name, Span::call_site());
+/// Target for a placeholder bound to explicit argument `index`: `target` the first time,
+/// then an equality check against the value stored by that first placeholder.
+fn argument_target<'a>(
+    state: &mut ParsingState<'a, '_>,
+    index: usize,
+    target: Target<'a>,
+) -> Result<Target<'a>, TokenStream> {
+    let arg_expr = match target {
+        Target::Argument(arg_expr, _)
+        | Target::Positional(arg_expr, _)
+        | Target::NamedArgument(arg_expr, _) => arg_expr,
+        _ => unreachable!("argument targets only"),
+    };
+    match state.arg_depths[index] {
+        None => {
+            state.arg_depths[index] = Some(state.depth);
+            Ok(target)
+        }
+        Some(0) => Ok(Target::Equal(target.description(), arg_expr)),
+        Some(_) => Err(syn::Error::new(
+            state.format_lit.span(),
+            format!(
+                "{} refers to an argument first captured inside an optional section, \
+                 so it cannot be captured again",
+                target.description()
+            ),
+        )
+        .to_compile_error()
+        .into()),
+    }
+}
 
-    quote! {
-        if let Some(pos) = remaining.find(#separator) {
-            let slice = &remaining[..pos];
-            match slice.parse() {
-                Ok(parsed) => {
-                    #ident = parsed;
+/// Generate code for an optional section: on failure, `remaining` and the captures inside
+/// are restored and the error is dropped.
+///
+/// Collected slots inside are reset to `None`; assignments are staged and only performed
+/// once the outermost section matches.
+fn generate_optional_section<'a>(
+    section: &'a [FormatToken],
+    state: &mut ParsingState<'a, '_>,
+    ends_format: bool,
+) -> Result<proc_macro2::TokenStream, TokenStream> {
+    let first_slot = state.slots.len();
+    let first_staged = state.staged.len();
+
+    state.depth += 1;
+    let generated = generate_sequence(section, state, ends_format)?;
+    state.depth -= 1;
+
+    let mut resets: Vec<&Ident> = state.slots[first_slot..]
+        .iter()
+        .map(|slot| &slot.ident)
+        .collect();
+    let mut declarations = Vec::new();
+    let mut commits = Vec::new();
+    if state.depth == 0 {
+        for (ident, target, ty) in &state.staged[first_staged..] {
+            declarations.push(match ty {
+                Some(ty) => quote! { let mut #ident: Option<#ty> = None; },
+                None => quote! { let mut #ident = None; },
+            });
+            let assign = target.assign();
+            commits.push(quote! {
+                if let Some(parsed) = #ident {
+                    #assign
                 }
-                Err(error) => {
-                    result = result.and(Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!("Failed to parse variable '{}' from {:?}: {}", #name, slice, error)
-                    )));
+            });
+        }
+    } else {
+        resets.extend(state.staged[first_staged..].iter().map(|(ident, ..)| ident));
+    }
+
+    let saved_result = format_ident!("saved_result", span = Span::mixed_site());
+    let saved_remaining = format_ident!("saved_remaining", span = Span::mixed_site());
+    let code = quote! {{
+        #(#declarations)*
+        let #saved_result = std::mem::replace(&mut result, Ok(()));
+        let #saved_remaining = remaining;
+        #(#generated)*
+        if result.is_ok() {
+            #(#commits)*
+        } else {
+            remaining = #saved_remaining;
+            #(#resets = None;)*
+        }
+        result = #saved_result;
+    }};
+
+    if state.depth == 0 {
+        state.staged.truncate(first_staged);
+    }
+    Ok(code)
+}
+
+/// Generate code parsing `slice` and storing it into the field's target.
+///
+/// `source` prefixes the slice in the error message (e.g. `"remaining input "`).
+/// Byte input is checked to be UTF-8 first, unless the field borrows raw `&[u8]`.
+/// Untyped discards only consume the slice.
+fn generate_store(field: &Field, input: Input, source: &str) -> proc_macro2::TokenStream {
+    if matches!(field.target, Target::Discard(_)) && field.ty.is_none() {
+        return quote! { let _ = slice; };
+    }
+    if let Some(separator) = field.repeat {
+        return generate_repeated_store(field, input, source, separator);
+    }
+
+    let description = field.target.description();
+    let assign = field.target.assign();
+
+    let store_text = match borrowed_slice(field.ty.as_ref()) {
+        Some(Input::Bytes) => {
+            return match input {
+                Input::Str => quote! { let parsed: &[u8] = slice.as_bytes(); #assign },
+                Input::Bytes => quote! { let parsed: &[u8] = slice; #assign },
+            };
+        }
+        Some(Input::Str) => quote! { let parsed: &str = slice; #assign },
+        None => {
+            let message = format!("Failed to parse {{}} from {}{{:?}}: {{}}", source);
+            let parse = match (field.radix, &field.ty) {
+                (Some(radix), ty) => generate_radix_parse(radix, ty.as_ref()),
+                (None, Some(ty)) => quote! { slice.parse::<#ty>() },
+                (None, None) => quote! { slice.parse() },
+            };
+            let parse_error = generate_error(
+                quote! { InvalidInput },
+                quote! { #message, #description, slice, error },
+            );
+            quote! {
+                match #parse {
+                    Ok(parsed) => {
+                        #assign
+                    }
+                    Err(error) => {
+                        result = result.and(Err(#parse_error));
+                    }
+                }
+            }
+        }
+    };
+
+    match input {
+        Input::Str => store_text,
+        Input::Bytes => {
+            let message = format!("{{}} is not valid UTF-8 in {}{{:?}}: {{}}", source);
+            let utf8_error = generate_error(
+                quote! { InvalidData },
+                quote! { #message, #description, String::from_utf8_lossy(slice), error },
+            );
+            quote! {
+                match std::str::from_utf8(slice) {
+                    Ok(slice) => {
+                        #store_text
+                    }
+                    Err(error) => {
+                        result = result.and(Err(#utf8_error));
+                    }
                 }
             }
-            remaining = &remaining[pos + #separator.len()..];
-        } else {
-            result = result.and(Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Expected separator {:?} for variable '{}' not found in remaining input: {:?}",
-                    #separator,
-                    #name,
-                    remaining
-                )
-            )));
         }
     }
 }
 
-/// Generate code for anonymous placeholder with separator.
-fn generate_anonymous_placeholder_with_separator(
-    arg_expr: &Expr,
-    placeholder_num: usize,
-    separator: &LitStr,
-) -> proc_macro2::TokenStream {
-    quote! {
-        if let Some(pos) = remaining.find(#separator) {
-            let slice = &remaining[..pos];
-            match slice.parse() {
-                Ok(parsed) => {
-                    *#arg_expr = parsed;
+/// `Result<T, String>` expression parsing `slice` as an integer in `radix`.
+///
+/// The magnitude is read as `u128`, then converted with `TryFrom`, so any integer type
+/// works without being known at expansion time.
+fn generate_radix_parse(radix: Radix, ty: Option<&Type>) -> proc_macro2::TokenStream {
+    let prefixes = |base: u32| match base {
+        16 => ("0x", "0X"),
+        8 => ("0o", "0O"),
+        _ => ("0b", "0B"),
+    };
+    let strip = |base: u32| {
+        let (lower, upper) = prefixes(base);
+        quote! { unsigned.strip_prefix(#lower).or_else(|| unsigned.strip_prefix(#upper)) }
+    };
+
+    let digits = match radix {
+        Radix::Base { base, prefixed } => {
+            let stripped = strip(base);
+            if prefixed {
+                let (prefix, _) = prefixes(base);
+                quote! {
+                    match #stripped {
+                        Some(digits) => Ok((digits, #base)),
+                        None => Err(format!("missing {:?} prefix", #prefix)),
+                    }
                 }
-                Err(error) => {
-                    result = result.and(Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        format!(
-                            "Failed to parse anonymous placeholder #{} from {:?}: {}",
-                            #placeholder_num,
-                            slice,
-                            error
-                        )
-                    )));
+            } else {
+                quote! { Ok((#stripped.unwrap_or(unsigned), #base)) }
+            }
+        }
+        Radix::Auto => {
+            let (hex, octal, binary) = (strip(16), strip(8), strip(2));
+            quote! {
+                if let Some(digits) = #hex {
+                    Ok((digits, 16))
+                } else if let Some(digits) = #octal {
+                    Ok((digits, 8))
+                } else if let Some(digits) = #binary {
+                    Ok((digits, 2))
+                } else if unsigned.len() > 1 && unsigned.starts_with('0') {
+                    Ok((&unsigned[1..], 8))
+                } else {
+                    Ok((unsigned, 10))
                 }
             }
+        }
+    };
+
+    let value = quote! {
+        {
+            let (negative, unsigned) = match slice.strip_prefix('-') {
+                Some(unsigned) => (true, unsigned),
+                None => (false, slice.strip_prefix('+').unwrap_or(slice)),
+            };
+            let digits: Result<(&str, u32), String> = #digits;
+            match digits {
+                Ok((digits, _)) if digits.starts_with(['+', '-']) => {
+                    Err("invalid digit found in string".to_string())
+                }
+                Ok((digits, base)) => match u128::from_str_radix(digits, base) {
+                    Ok(magnitude) if negative => 0i128
+                        .checked_sub_unsigned(magnitude)
+                        .and_then(|value| TryFrom::try_from(value).ok())
+                        .ok_or_else(|| "number too small to fit in target type".to_string()),
+                    Ok(magnitude) => TryFrom::try_from(magnitude)
+                        .ok()
+                        .ok_or_else(|| "number too large to fit in target type".to_string()),
+                    Err(error) => Err(error.to_string()),
+                },
+                Err(error) => Err(error),
+            }
+        }
+    };
+
+    match ty {
+        Some(ty) => quote! {{ let value: Result<#ty, String> = #value; value }},
+        None => value,
+    }
+}
+
+/// Generate code splitting `slice` on `separator` and storing the parsed elements as a `Vec`.
+///
+/// The vector is stored only when every element parses; an empty slice stores an empty one.
+fn generate_repeated_store(
+    field: &Field,
+    input: Input,
+    source: &str,
+    separator: &str,
+) -> proc_macro2::TokenStream {
+    let values = format_ident!("values", span = Span::mixed_site());
+    let count = format_ident!("count", span = Span::mixed_site());
+    let rest = format_ident!("rest", span = Span::mixed_site());
+    let next = format_ident!("next", span = Span::mixed_site());
+
+    let element_ty = field.ty.as_ref().and_then(vec_element).cloned();
+    let declaration = match &element_ty {
+        Some(ty) => quote! { let mut #values: Vec<#ty> = Vec::new(); },
+        None => quote! { let mut #values = Vec::new(); },
+    };
+    let element = Field {
+        target: Target::Element(field.target.description(), values.clone()),
+        ty: element_ty,
+        radix: field.radix,
+        repeat: None,
+        last: false,
+        ignore_case: field.ignore_case,
+    };
+    let store_element = generate_store(&element, input, source);
+    let assign = field.target.assign();
+    let separator = input.literal(separator);
+    let find = input.find_in(&quote! { #rest }, &separator, field.ignore_case);
+
+    quote! {{
+        #declaration
+        let mut #count: usize = 0;
+        if !slice.is_empty() {
+            let mut #rest = slice;
+            loop {
+                let (slice, #next) = match #find {
+                    Some(pos) => (&#rest[..pos], Some(&#rest[pos + #separator.len()..])),
+                    None => (#rest, None),
+                };
+                #count += 1;
+                #store_element
+                match #next {
+                    Some(#next) => #rest = #next,
+                    None => break,
+                }
+            }
+        }
+        if #values.len() == #count {
+            let parsed = #values;
+            #assign
+        }
+    }}
+}
+
+/// Generate code for placeholder with separator.
+///
+/// With a backtracking `choice` point, the field ends at the separator occurrence picked by
+/// `choices[choice]` (0 for the first) and records whether a later occurrence exists.
+fn generate_placeholder_with_separator(
+    field: &Field,
+    input: Input,
+    separator: &str,
+    ignore_case: bool,
+    choice: Option<usize>,
+) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let shown_remaining = input.show(quote! { remaining });
+    // Next occurrence can only start after the first char of this one
+    let step = separator.chars().next().map_or(1, char::len_utf8);
+    let shown_separator = input.show(input.literal(separator));
+    let separator = input.literal(separator);
+
+    let found = match choice {
+        None => input.find(&separator, ignore_case),
+        Some(choice) => {
+            let BacktrackIdents {
+                choices,
+                more,
+                reached,
+                ..
+            } = BacktrackIdents::new();
+            let find_after = input.find_in(&quote! { remaining[from..] }, &separator, ignore_case);
+            let find_later = input.find_in(
+                &quote! { remaining[pos + #step..] },
+                &separator,
+                ignore_case,
+            );
+            quote! {{
+                let mut skip = #choices[#choice];
+                let mut from = 0;
+                let found = loop {
+                    match #find_after {
+                        Some(pos) if skip > 0 => {
+                            skip -= 1;
+                            from += pos + #step;
+                        }
+                        Some(pos) => break Some(from + pos),
+                        None => break None,
+                    }
+                };
+                if result.is_ok() {
+                    #reached = #choice + 1;
+                    #more[#choice] = found.is_some_and(|pos| #find_later.is_some());
+                }
+                found
+            }}
+        }
+    };
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected separator {:?} for {} not found in remaining input: {:?}",
+            #shown_separator,
+            #description,
+            #shown_remaining
+        },
+    );
+    quote! {
+        if let Some(pos) = #found {
+            let slice = &remaining[..pos];
+            #store
             remaining = &remaining[pos + #separator.len()..];
         } else {
-            result = result.and(Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Expected separator {:?} for anonymous placeholder #{} not found in remaining input: {:?}",
-                    #separator,
-                    #placeholder_num,
-                    remaining
-                )
-            )));
+            result = result.and(Err(#error));
         }
     }
 }
 
-/// Generate code for fixed text matching at current position.
-fn generate_fixed_text_match(text: &LitStr) -> proc_macro2::TokenStream {
-    quote! {
-        if let Some(pos) = remaining.find(#text) {
-            if pos == 0 {
-                remaining = &remaining[#text.len()..];
-            } else {
-                result = result.and(Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!(
-                        "Expected text {:?} at current position, but found it at offset {}. \
-                         Remaining input: {:?}",
-                        #text,
-                        pos,
-                        remaining
-                    )
-                )));
+/// First literal text of `tokens` past the placeholders, ending the search range of a
+/// `{name:last}` field. In C whitespace mode, whitespace runs match anything and are skipped.
+/// `None` at an optional section, an alternation or the end of the format.
+fn next_literal(tokens: &[FormatToken], c_whitespace: bool) -> Option<&str> {
+    for token in tokens {
+        match token {
+            FormatToken::Text(text) if c_whitespace => {
+                let run = whitespace_runs(text)
+                    .into_iter()
+                    .find(|&(is_whitespace, _)| !is_whitespace);
+                if let Some((_, run)) = run {
+                    return Some(run);
+                }
             }
-        } else {
-            result = result.and(Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Required text separator {:?} not found. Remaining input: {:?}",
-                    #text,
-                    remaining
-                )
-            )));
+            FormatToken::Text(text) => return Some(text),
+            FormatToken::Placeholder(_) => {}
+            FormatToken::Optional(_) | FormatToken::Alternation(_) => return None,
         }
     }
+    None
 }
 
-/// Generate code for final named placeholder (consumes rest of input).
-fn generate_final_named_placeholder(name: &str) -> proc_macro2::TokenStream {
-    let ident = Ident::new(name, Span::call_site());
+/// Generate code for `{name:last}` placeholder: ends at the last separator before `bound`,
+/// the next literal text found after the first separator (the end of input without one).
+fn generate_last_separator_placeholder(
+    field: &Field,
+    input: Input,
+    separator: &str,
+    bound: Option<&str>,
+    ignore_case: bool,
+) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let shown_separator = input.show(input.literal(separator));
+    let shown_remaining = input.show(quote! { remaining });
+    let separator = input.literal(separator);
+    let find = input.find(&separator, ignore_case);
+    let end = match bound {
+        Some(bound) => {
+            let bound = input.literal(bound);
+            let find_bound = input.find_in(&quote! { remaining[from..] }, &bound, ignore_case);
+            quote! {{
+                let from = first + #separator.len();
+                #find_bound.map_or(remaining.len(), |pos| from + pos)
+            }}
+        }
+        None => quote! { remaining.len() },
+    };
+    let rfind = input.rfind_in(&quote! { remaining[..end] }, &separator, ignore_case);
 
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected separator {:?} for {} not found in remaining input: {:?}",
+            #shown_separator,
+            #description,
+            #shown_remaining
+        },
+    );
     quote! {
-        match remaining.parse() {
-            Ok(parsed) => {
-                #ident = parsed;
+        match #find.map(|first| #end).and_then(|end| #rfind) {
+            Some(pos) => {
+                let slice = &remaining[..pos];
+                #store
+                remaining = &remaining[pos + #separator.len()..];
             }
-            Err(error) => {
-                result = result.and(Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!("Failed to parse variable '{}' from remaining input {:?}: {}", #name, remaining, error)
-                )));
+            None => {
+                result = result.and(Err(#error));
             }
         }
-        remaining = "";
     }
 }
 
-/// Generate code for final anonymous placeholder (consumes rest of input).
-fn generate_final_anonymous_placeholder(
-    arg_expr: &Expr,
-    placeholder_num: usize,
+/// Split text into alternating runs of whitespace (`true`) and other chars (`false`).
+fn whitespace_runs(text: &str) -> Vec<(bool, &str)> {
+    let mut runs = Vec::new();
+    let mut rest = text;
+
+    while let Some(first) = rest.chars().next() {
+        let is_whitespace = first.is_whitespace();
+        let end = rest
+            .find(|c: char| c.is_whitespace() != is_whitespace)
+            .unwrap_or(rest.len());
+        runs.push((is_whitespace, &rest[..end]));
+        rest = &rest[end..];
+    }
+
+    runs
+}
+
+/// Generate code for placeholder ended by whitespace, like C's `%s`, or by the `next`
+/// literal text if that comes first.
+fn generate_whitespace_delimited_placeholder(
+    field: &Field,
+    input: Input,
+    next: Option<&proc_macro2::TokenStream>,
+    ignore_case: bool,
+) -> proc_macro2::TokenStream {
+    let store = generate_store(field, input, "");
+    let span_end = input.span_end(&input.is_whitespace());
+    let end = match next {
+        Some(next) => {
+            let find = input.find(next, ignore_case);
+            quote! { #find.map_or(#span_end, |pos| pos.min(#span_end)) }
+        }
+        None => span_end,
+    };
+
+    quote! {{
+        let end = #end;
+        let slice = &remaining[..end];
+        #store
+        remaining = &remaining[end..];
+    }}
+}
+
+/// Generate code for fixed-width placeholder (consumes exactly `width` chars or bytes).
+fn generate_fixed_width_placeholder(
+    field: &Field,
+    input: Input,
+    width: usize,
 ) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let width_end = input.width_end(width);
+    let message = format!(
+        "Expected {{}} {} for {{}} but remaining input is shorter: {{:?}}",
+        input.width_unit()
+    );
+    let shown_remaining = input.show(quote! { remaining });
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! { #message, #width, #description, #shown_remaining },
+    );
     quote! {
-        match remaining.parse() {
-            Ok(parsed) => {
-                *#arg_expr = parsed;
+        match #width_end {
+            Some(end) => {
+                let slice = &remaining[..end];
+                #store
+                remaining = &remaining[end..];
             }
-            Err(error) => {
-                result = result.and(Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidInput,
-                    format!(
-                        "Failed to parse anonymous placeholder #{} from remaining input {:?}: {}",
-                        #placeholder_num,
-                        remaining,
-                        error
+            None => {
+                result = result.and(Err(#error));
+            }
+        }
+    }
+}
+
+/// `matches!` pattern for the chars (or Latin-1 bytes) of a set, e.g. `'a'..='z' | '_'`.
+fn generate_class_pattern(
+    class: &CharClass,
+    input: Input,
+    format_lit: &LitStr,
+) -> Result<proc_macro2::TokenStream, TokenStream> {
+    let mut alternatives = Vec::with_capacity(class.ranges.len());
+
+    for &(start, end) in &class.ranges {
+        let (start, end) = match input {
+            Input::Str => (
+                Literal::character(start).to_token_stream(),
+                Literal::character(end).to_token_stream(),
+            ),
+            Input::Bytes => match (u8::try_from(start), u8::try_from(end)) {
+                (Ok(start), Ok(end)) => (
+                    Literal::u8_suffixed(start).to_token_stream(),
+                    Literal::u8_suffixed(end).to_token_stream(),
+                ),
+                _ => {
+                    return Err(syn::Error::new(
+                        format_lit.span(),
+                        format!(
+                            "Character set {} matches bytes, so its chars must be \
+                             at most U+00FF (Latin-1)",
+                            class.source
+                        ),
                     )
-                )));
+                    .to_compile_error()
+                    .into());
+                }
+            },
+        };
+        alternatives.push(quote! { #start..=#end });
+    }
+
+    Ok(quote! { #(#alternatives)|* })
+}
+
+/// Generate code for character-set placeholder (longest non-empty run of set members).
+fn generate_char_class_placeholder(
+    field: &Field,
+    input: Input,
+    class: &CharClass,
+    pattern: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let stop = if class.negated {
+        quote! { matches!(c, #pattern) }
+    } else {
+        quote! { !matches!(c, #pattern) }
+    };
+    let span_end = input.span_end(&stop);
+    let source = &*class.source;
+    let shown_remaining = input.show(quote! { remaining });
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected {} to match character set {} at current position. \
+             Remaining input: {:?}",
+            #description,
+            #source,
+            #shown_remaining
+        },
+    );
+    quote! {
+        match #span_end {
+            0 => {
+                result = result.and(Err(#error));
+            }
+            end => {
+                let slice = &remaining[..end];
+                #store
+                remaining = &remaining[end..];
             }
         }
-        remaining = "";
     }
 }
 
+/// Generate code for quoted placeholder: a `"..."` or `'...'` string at the current position.
+///
+/// The field is the unescaped contents: a doubled quote or `\"` is a quote, `\n`, `\t`,
+/// `\r` and `\0` are control chars and a backslash before any other char keeps that char.
+fn generate_quoted_placeholder(field: &Field, input: Input) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let unescaped = format_ident!("unescaped", span = Span::mixed_site());
+    let chars = format_ident!("chars", span = Span::mixed_site());
+    let quote = format_ident!("quote", span = Span::mixed_site());
+    let (buffer, iter, quotes, backslash, escapes, contents) = match input {
+        Input::Str => (
+            quote! { String::new() },
+            quote! { remaining.char_indices() },
+            quote! { '"' | '\'' },
+            quote! { '\\' },
+            quote! { 'n' => '\n', 't' => '\t', 'r' => '\r', '0' => '\0', },
+            quote! { #unescaped.as_str() },
+        ),
+        Input::Bytes => (
+            quote! { Vec::new() },
+            quote! { remaining.iter().copied().enumerate() },
+            quote! { b'"' | b'\'' },
+            quote! { b'\\' },
+            quote! { b'n' => b'\n', b't' => b'\t', b'r' => b'\r', b'0' => b'\0', },
+            quote! { #unescaped.as_slice() },
+        ),
+    };
+    let shown_remaining = input.show(quote! { remaining });
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected {} to be a quoted string at current position. \
+             Remaining input: {:?}",
+            #description,
+            #shown_remaining
+        },
+    );
+    quote! {{
+        let mut #unescaped = #buffer;
+        let mut end = None;
+        let mut #chars = #iter.peekable();
+        if let Some((_, #quote @ (#quotes))) = #chars.next() {
+            while let Some((index, c)) = #chars.next() {
+                if c == #quote {
+                    if #chars.next_if(|&(_, next)| next == #quote).is_none() {
+                        end = Some(index + 1);
+                        break;
+                    }
+                    #unescaped.push(#quote);
+                } else if c == #backslash {
+                    match #chars.next() {
+                        Some((_, escaped)) => #unescaped.push(match escaped {
+                            #escapes
+                            other => other,
+                        }),
+                        None => break,
+                    }
+                } else {
+                    #unescaped.push(c);
+                }
+            }
+        }
+        match end {
+            Some(end) => {
+                let slice = #contents;
+                #store
+                remaining = &remaining[end..];
+            }
+            None => {
+                result = result.and(Err(#error));
+            }
+        }
+    }}
+}
+
+/// Regex source anchored at the current position, checked to compile for `input`.
+///
+/// The exact source emitted is the one checked. When a trailing `(?x)` comment swallows the
+/// closing `)`, the group is closed on a new line, which ends the comment and x mode ignores.
+fn anchored_regex(regex: &str, input: Input, format_lit: &LitStr) -> Result<String, TokenStream> {
+    #[cfg(feature = "regex")]
+    let checked = {
+        let compile = |pattern: &str| match input {
+            Input::Str => regex::Regex::new(pattern).map(drop),
+            Input::Bytes => regex::bytes::Regex::new(pattern).map(drop),
+        };
+        compile(regex)
+            .and_then(|()| {
+                let anchored = format!("\\A(?:{})", regex);
+                match compile(&anchored) {
+                    Ok(()) => Ok(anchored),
+                    Err(_) => {
+                        let anchored = format!("\\A(?:{}\n)", regex);
+                        compile(&anchored).map(|()| anchored)
+                    }
+                }
+            })
+            .map_err(|error| format!("Invalid regex /{}/ in placeholder: {}", regex, error))
+    };
+
+    #[cfg(not(feature = "regex"))]
+    let checked = {
+        let _ = input;
+        Err(format!(
+            "Regex placeholder /{}/ requires the `regex` feature of scanf",
+            regex
+        ))
+    };
+
+    checked.map_err(|message| {
+        syn::Error::new(format_lit.span(), message)
+            .to_compile_error()
+            .into()
+    })
+}
+
+/// Generate code for regex placeholder (leftmost match at the current position).
+///
+/// The regex is compiled once, on first use, into a `static` of the expansion.
+fn generate_regex_placeholder(
+    field: &Field,
+    input: Input,
+    regex: &str,
+    anchored: &str,
+) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let regex_type = match input {
+        Input::Str => quote! { ::regex::Regex },
+        Input::Bytes => quote! { ::regex::bytes::Regex },
+    };
+    let shown_remaining = input.show(quote! { remaining });
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected {} to match regex /{}/ at current position. \
+             Remaining input: {:?}",
+            #description,
+            #regex,
+            #shown_remaining
+        },
+    );
+    quote! {{
+        let pattern = {
+            static PATTERN: std::sync::OnceLock<#regex_type> = std::sync::OnceLock::new();
+            PATTERN.get_or_init(|| {
+                <#regex_type>::new(#anchored).expect("regex validated at compile time")
+            })
+        };
+        match pattern.find(remaining) {
+            Some(found) => {
+                let slice = &remaining[..found.end()];
+                #store
+                remaining = &remaining[found.end()..];
+            }
+            None => {
+                result = result.and(Err(#error));
+            }
+        }
+    }}
+}
+
+/// Generate code for an alternation placeholder: the first alternative found at the current
+/// position, or, after a `previous` field without delimiter, the earliest one in the input.
+///
+/// The `previous` field takes the input before the chosen alternative.
+fn generate_alternation(
+    field: &Field,
+    previous: Option<&Field>,
+    input: Input,
+    alternatives: &[Box<str>],
+    ignore_case: bool,
+) -> proc_macro2::TokenStream {
+    let description = field.target.description();
+    let store = generate_store(field, input, "");
+    let alternative = format_ident!("alternative", span = Span::mixed_site());
+    let alternatives_ident = format_ident!("alternatives", span = Span::mixed_site());
+    let count = alternatives.len();
+    let literals = alternatives.iter().map(|text| input.literal(text));
+    let declaration = match input {
+        Input::Str => quote! { let #alternatives_ident: [&str; #count] = [#(#literals),*]; },
+        Input::Bytes => quote! { let #alternatives_ident: [&[u8]; #count] = [#(#literals),*]; },
+    };
+    let shown_alternatives = alternatives
+        .iter()
+        .map(|text| format!("{:?}", text))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let shown_remaining = input.show(quote! { remaining });
+    let store_alternative = quote! {
+        let slice = &remaining[..#alternative.len()];
+        #store
+        remaining = &remaining[#alternative.len()..];
+    };
+
+    let Some(previous) = previous else {
+        let starts_with = if ignore_case {
+            quote! {
+                remaining
+                    .get(..#alternative.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(#alternative))
+            }
+        } else {
+            quote! { remaining.starts_with(#alternative) }
+        };
+        let error = generate_error(
+            quote! { InvalidInput },
+            quote! {
+                "Expected one of [{}] for {} at current position. \
+                 Remaining input: {:?}",
+                #shown_alternatives,
+                #description,
+                #shown_remaining
+            },
+        );
+        return quote! {{
+            #declaration
+            match #alternatives_ident.into_iter().find(|&#alternative| #starts_with) {
+                Some(#alternative) => {
+                    #store_alternative
+                }
+                None => {
+                    result = result.and(Err(#error));
+                }
+            }
+        }};
+    };
+
+    let previous_description = previous.target.description();
+    let store_previous = generate_store(previous, input, "");
+    let find = input.find(&alternative.to_token_stream(), ignore_case);
+
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected one of [{}] for {} after {} not found in remaining input: {:?}",
+            #shown_alternatives,
+            #description,
+            #previous_description,
+            #shown_remaining
+        },
+    );
+    quote! {{
+        #declaration
+        let earliest = #alternatives_ident
+            .into_iter()
+            .filter_map(|#alternative| #find.map(|pos| (pos, #alternative)))
+            .min_by_key(|&(pos, _)| pos);
+        match earliest {
+            Some((pos, #alternative)) => {
+                let slice = &remaining[..pos];
+                #store_previous
+                remaining = &remaining[pos..];
+                #store_alternative
+            }
+            None => {
+                result = result.and(Err(#error));
+            }
+        }
+    }}
+}
+
+/// Generate code for fixed text matching at current position.
+fn generate_fixed_text_match(
+    input: Input,
+    text: &proc_macro2::TokenStream,
+    ignore_case: bool,
+) -> proc_macro2::TokenStream {
+    let find = input.find(text, ignore_case);
+    let shown_text = input.show(text.clone());
+    let shown_remaining = input.show(quote! { remaining });
+
+    let misplaced = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Expected text {:?} at current position, but found it at offset {}. \
+             Remaining input: {:?}",
+            #shown_text,
+            pos,
+            #shown_remaining
+        },
+    );
+    let missing = generate_error(
+        quote! { InvalidInput },
+        quote! {
+            "Required text separator {:?} not found. Remaining input: {:?}",
+            #shown_text,
+            #shown_remaining
+        },
+    );
+    quote! {
+        if let Some(pos) = #find {
+            if pos == 0 {
+                remaining = &remaining[#text.len()..];
+            } else {
+                result = result.and(Err(#misplaced));
+            }
+        } else {
+            result = result.and(Err(#missing));
+        }
+    }
+}
+
+/// Generate code for final placeholder (consumes rest of input).
+fn generate_final_placeholder(field: &Field, input: Input) -> proc_macro2::TokenStream {
+    let store = generate_store(field, input, "remaining input ");
+    let empty = input.empty();
+
+    quote! {
+        let slice = remaining;
+        #store
+        remaining = #empty;
+    }
+}
+
+/// Create error for a `{name:last}` placeholder not followed by literal text.
+fn make_last_without_text_error(format_lit: &LitStr) -> TokenStream {
+    syn::Error::new(
+        format_lit.span(),
+        "A '{name:last}' placeholder ends at the last occurrence of the text after it, \
+         so it must be followed by literal text. Example: '{path:last}:{port}'",
+    )
+    .to_compile_error()
+    .into()
+}
+
 /// Create error for missing anonymous placeholder argument.
 fn make_missing_argument_error(
     position: usize,
@@ -259,14 +1858,25 @@ fn make_missing_argument_error(
     .into()
 }
 
-/// Generate complete scanf implementation: tokenize, validate, codegen.
+/// Tokenize and reject formats without content.
 ///
-/// Errors on empty format, no content, unused args, or validation failures.
-pub fn generate_scanf_implementation(
+/// A leading flag group is stripped and turned on in `options`: `i` (literal text ignores
+/// ASCII case) and `b` (backtracking), as in `(?i)`, `(?b)` or `(?ib)`.
+fn tokenize_non_empty(
     format_lit: &LitStr,
-    explicit_args: &[&Expr],
-) -> Result<Vec<proc_macro2::TokenStream>, TokenStream> {
-    let format_str = format_lit.value();
+    mut options: MatchOptions,
+) -> Result<(Vec<FormatToken>, MatchOptions), TokenStream> {
+    let mut format_str = format_lit.value();
+    let flags = format_str
+        .strip_prefix("(?")
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(flags, _)| flags)
+        .filter(|flags| !flags.is_empty() && flags.chars().all(|flag| matches!(flag, 'i' | 'b')));
+    if let Some(flags) = flags {
+        options.ignore_case |= flags.contains('i');
+        options.backtrack |= flags.contains('b');
+        format_str = format_str[flags.len() + 3..].to_string();
+    }
 
     if format_str.is_empty() {
         return Err(syn::Error::new(
@@ -288,21 +1898,168 @@ pub fn generate_scanf_implementation(
         .into());
     }
 
-    let (generated, anon_index) = generate_parsing_code(&tokens, explicit_args, format_lit)?;
+    Ok((tokens, options))
+}
 
-    if anon_index < explicit_args.len() {
-        let unused_count = explicit_args.len() - anon_index;
-        return Err(syn::Error::new(
-            explicit_args[anon_index].span(),
-            format!(
-                "Too many arguments: {} unused argument(s) provided. \
-                 The format string only has {} anonymous placeholder(s)",
-                unused_count, anon_index
+/// Generate complete scanf implementation: tokenize, validate, codegen.
+///
+/// Errors on empty format, no content, unused args, or validation failures.
+pub fn generate_scanf_implementation(
+    format_lit: &LitStr,
+    explicit_args: &ExplicitArgs,
+    input: Input,
+    options: MatchOptions,
+) -> Result<Vec<proc_macro2::TokenStream>, TokenStream> {
+    let (tokens, options) = tokenize_non_empty(format_lit, options)?;
+
+    let ParsingCode {
+        generated,
+        used_args,
+        ..
+    } = generate_parsing_code(
+        &tokens,
+        &Captures::Assign(explicit_args),
+        input,
+        options,
+        format_lit,
+    )?;
+
+    if let Some(first_unused) = used_args.iter().position(|&used| !used) {
+        let positional_count = explicit_args.positional.len();
+        let (span, message) = match first_unused.checked_sub(positional_count) {
+            Some(named_index) => {
+                let (name, _) = &explicit_args.named[named_index];
+                (
+                    name.span(),
+                    format!(
+                        "Named argument '{}' is not used by any '{{{}}}' placeholder",
+                        name, name
+                    ),
+                )
+            }
+            None => (
+                explicit_args.positional[first_unused].span(),
+                format!(
+                    "Too many arguments: {} unused argument(s) provided. \
+                     Argument {} is not referred to by any '{{}}' or '{{N}}' placeholder",
+                    used_args[..positional_count]
+                        .iter()
+                        .filter(|&&used| !used)
+                        .count(),
+                    first_unused
+                ),
             ),
-        )
-        .to_compile_error()
-        .into());
+        };
+        return Err(syn::Error::new(span, message).to_compile_error().into());
     }
 
     Ok(generated)
 }
+
+/// Generate implementation collecting every placeholder into a [`Slot`].
+///
+/// Generated code declares the slots first; pair with [`generate_collected_result`].
+pub fn generate_capture_implementation(
+    format_lit: &LitStr,
+    options: MatchOptions,
+) -> Result<(Vec<proc_macro2::TokenStream>, Vec<Slot>), TokenStream> {
+    let (tokens, options) = tokenize_non_empty(format_lit, options)?;
+    let ParsingCode {
+        generated, slots, ..
+    } = generate_parsing_code(&tokens, &Captures::Collect, Input::Str, options, format_lit)?;
+
+    let mut code = Vec::with_capacity(slots.len() + generated.len());
+    for slot in &slots {
+        let ident = &slot.ident;
+        code.push(match &slot.ty {
+            Some(ty) => quote! { let mut #ident: Option<#ty> = None; },
+            None => quote! { let mut #ident = None; },
+        });
+    }
+    code.extend(generated);
+
+    Ok((code, slots))
+}
+
+/// Expression turning `result` and filled slots into `io::Result<(T1, T2, ...)>`.
+///
+/// Slots inside optional sections are returned as `Option<T>`.
+pub fn generate_collected_result(slots: &[Slot]) -> proc_macro2::TokenStream {
+    let idents: Vec<_> = slots.iter().map(|slot| &slot.ident).collect();
+
+    if idents.is_empty() {
+        return quote! { result };
+    }
+
+    let patterns = slots.iter().map(|slot| {
+        let ident = &slot.ident;
+        if slot.optional {
+            quote! { #ident }
+        } else {
+            quote! { Some(#ident) }
+        }
+    });
+
+    let unfilled = if slots.iter().all(|slot| slot.optional) {
+        None
+    } else {
+        Some(quote! { _ => unreachable!("every placeholder is filled when parsing succeeds"), })
+    };
+
+    quote! {
+        match (result, #(#idents),*) {
+            (Ok(()), #(#patterns),*) => Ok((#(#idents,)*)),
+            (Err(error), ..) => Err(error),
+            #unfilled
+        }
+    }
+}
+
+/// Like [`generate_collected_result`], but a single value is returned bare instead of `(T,)`.
+pub fn generate_returned_values(slots: &[Slot]) -> proc_macro2::TokenStream {
+    let collected = generate_collected_result(slots);
+
+    if slots.len() == 1 {
+        quote! { (#collected).map(|(value,)| value) }
+    } else {
+        collected
+    }
+}
+
+/// Generate code rejecting unparsed trailing input (whole-input matches).
+fn generate_end_of_input_check() -> proc_macro2::TokenStream {
+    let error = generate_error(
+        quote! { InvalidInput },
+        quote! { "Unexpected trailing input: {:?}", remaining },
+    );
+    quote! {
+        if !remaining.is_empty() {
+            result = result.and(Err(#error));
+        }
+    }
+}
+
+/// Require every slot to be named and unique (macros introducing bindings).
+pub fn check_named_slots(
+    slots: &[Slot],
+    format_lit: &LitStr,
+    macro_name: &str,
+) -> Result<(), TokenStream> {
+    for (index, slot) in slots.iter().enumerate() {
+        let message = match &slot.name {
+            None => format!(
+                "{} binds placeholders by name; anonymous placeholder '{{}}' has nothing to bind to. \
+                 Use a named placeholder (e.g., '{{value}}' or '{{value:i32}}')",
+                macro_name
+            ),
+            Some(name) if slots[..index].iter().any(|s| s.name.as_ref() == Some(name)) => {
+                format!("Placeholder '{}' is captured more than once", name)
+            }
+            Some(_) => continue,
+        };
+        return Err(syn::Error::new(format_lit.span(), message)
+            .to_compile_error()
+            .into());
+    }
+    Ok(())
+}
@@ -7,6 +7,10 @@ pub const MAX_FORMAT_STRING_LEN: usize = 10_000;
 pub const MAX_TOKENS: usize = 256;
 /// Max identifier length (chars).
 pub const MAX_IDENTIFIER_LEN: usize = 128;
+/// Max placeholder content length (chars), identifier plus `:spec`.
+pub const MAX_PLACEHOLDER_LEN: usize = 512;
+/// Max input bytes re-scanned by the retries of a backtracking parse (`(?b)`) at runtime.
+pub const MAX_BACKTRACK_SCAN: usize = 1 << 20;
 
 // Memory pre-allocation hints
 /// Initial token vector capacity.
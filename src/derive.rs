@@ -0,0 +1,254 @@
+//! `#[derive(Scanf)]`: `FromStr` implementation from a `#[scanf("...")]` format.
+
+use crate::codegen::{
+    MatchOptions, Slot, check_named_slots, generate_capture_implementation,
+    generate_collected_result,
+};
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr, spanned::Spanned};
+
+/// Expand `#[derive(Scanf)]` into `impl FromStr`.
+///
+/// Enum variants are tried in declaration order; the first full match wins.
+pub fn expand_derive_scanf(input: &DeriveInput) -> Result<proc_macro2::TokenStream, TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let format_lit = require_format_attribute(&input.attrs, name.span())?;
+            generate_fields_parser(
+                &quote! { Self },
+                &name.to_string(),
+                &data.fields,
+                &format_lit,
+            )?
+        }
+        Data::Enum(data) => {
+            if let Some(attr) = input
+                .attrs
+                .iter()
+                .find(|attr| attr.path().is_ident("scanf"))
+            {
+                return Err(make_error(
+                    attr.span(),
+                    "#[derive(Scanf)] on an enum takes one #[scanf(\"...\")] per variant"
+                        .to_string(),
+                ));
+            }
+
+            let mut attempts = Vec::with_capacity(data.variants.len());
+            for variant in &data.variants {
+                let variant_ident = &variant.ident;
+                let format_lit = require_format_attribute(&variant.attrs, variant_ident.span())?;
+                let parser = generate_fields_parser(
+                    &quote! { Self::#variant_ident },
+                    &variant_ident.to_string(),
+                    &variant.fields,
+                    &format_lit,
+                )?;
+                let variant_name = variant_ident.to_string();
+                attempts.push(quote! {
+                    match #parser {
+                        Ok(value) => return Ok(value),
+                        Err(error) => failures.push(format!("{}: {}", #variant_name, error)),
+                    }
+                });
+            }
+
+            let enum_name = name.to_string();
+            quote! {
+                let mut failures: Vec<String> = Vec::new();
+                #(#attempts)*
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "Input {:?} matches no variant of {} ({})",
+                        input,
+                        #enum_name,
+                        failures.join("; ")
+                    )
+                ))
+            }
+        }
+        Data::Union(_) => {
+            return Err(make_error(
+                name.span(),
+                "#[derive(Scanf)] supports structs and enums, not unions".to_string(),
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::std::io::Error;
+
+            fn from_str(input: &str) -> ::core::result::Result<Self, Self::Err> {
+                #body
+            }
+        }
+    })
+}
+
+/// Find the single `#[scanf("...")]` attribute.
+fn require_format_attribute(attrs: &[Attribute], span: Span) -> Result<LitStr, TokenStream> {
+    let mut found = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("scanf")) {
+        if found.is_some() {
+            return Err(make_error(
+                attr.span(),
+                "Duplicate #[scanf(...)] attribute".to_string(),
+            ));
+        }
+        let format_lit: LitStr = attr
+            .parse_args()
+            .map_err(|error| TokenStream::from(error.to_compile_error()))?;
+        found = Some(format_lit);
+    }
+
+    found.ok_or_else(|| {
+        make_error(
+            span,
+            "#[derive(Scanf)] requires a format attribute, e.g. #[scanf(\"{x},{y}\")]".to_string(),
+        )
+    })
+}
+
+/// Expression parsing the whole `input` into `path` (`Self` or `Self::Variant`).
+///
+/// Named fields take named placeholders, tuple fields take `{}` in order.
+/// `owner` names the struct or variant in error messages.
+fn generate_fields_parser(
+    path: &proc_macro2::TokenStream,
+    owner: &str,
+    fields: &Fields,
+    format_lit: &LitStr,
+) -> Result<proc_macro2::TokenStream, TokenStream> {
+    let options = MatchOptions {
+        whole_input: true,
+        ..MatchOptions::default()
+    };
+    let (generated, slots) = generate_capture_implementation(format_lit, options)?;
+    let constructor = generate_constructor(path, owner, fields, &slots, format_lit)?;
+    let collected = generate_collected_result(&slots);
+    let slot_idents = slots.iter().map(|slot| &slot.ident);
+
+    Ok(quote! {{
+        let mut result: std::io::Result<()> = Ok(());
+        let mut remaining = input;
+        #(#generated)*
+        (#collected).map(|(#(#slot_idents,)*)| #constructor)
+    }})
+}
+
+/// Build `path { field: slot, .. }` / `path(slot, ..)`, checking placeholders against fields.
+fn generate_constructor(
+    path: &proc_macro2::TokenStream,
+    owner: &str,
+    fields: &Fields,
+    slots: &[Slot],
+    format_lit: &LitStr,
+) -> Result<proc_macro2::TokenStream, TokenStream> {
+    match fields {
+        Fields::Named(named) => {
+            check_named_slots(slots, format_lit, "#[derive(Scanf)] with named fields")?;
+
+            for slot in slots {
+                let name = slot.name.as_deref().unwrap_or_default();
+                if !named.named.iter().any(|field| is_field_named(field, name)) {
+                    return Err(make_error(
+                        format_lit.span(),
+                        format!("Placeholder '{}' does not name a field of {}", name, owner),
+                    ));
+                }
+            }
+
+            let mut initializers = Vec::with_capacity(named.named.len());
+            for field in &named.named {
+                let ident = field.ident.as_ref().expect("named field has an identifier");
+                let Some(slot) = slots
+                    .iter()
+                    .find(|slot| is_field_named(field, slot.name.as_deref().unwrap_or_default()))
+                else {
+                    return Err(make_error(
+                        ident.span(),
+                        format!(
+                            "Field '{}' is never captured by the format string {:?}",
+                            ident,
+                            format_lit.value()
+                        ),
+                    ));
+                };
+                let slot_ident = &slot.ident;
+                initializers.push(quote! { #ident: #slot_ident });
+            }
+
+            Ok(quote! { #path { #(#initializers),* } })
+        }
+        Fields::Unnamed(unnamed) => {
+            if let Some(slot) = slots.iter().find(|slot| slot.name.is_some()) {
+                return Err(make_error(
+                    format_lit.span(),
+                    format!(
+                        "Tuple fields are captured in order with anonymous placeholders '{{}}', \
+                         found named placeholder '{}'",
+                        slot.name.as_deref().unwrap_or_default()
+                    ),
+                ));
+            }
+            check_field_count(unnamed.unnamed.len(), slots.len(), fields, format_lit)?;
+
+            let slot_idents = slots.iter().map(|slot| &slot.ident);
+            Ok(quote! { #path(#(#slot_idents),*) })
+        }
+        Fields::Unit => {
+            check_field_count(0, slots.len(), fields, format_lit)?;
+            Ok(quote! { #path })
+        }
+    }
+}
+
+/// Field matches a placeholder name (raw identifiers compare without `r#`).
+fn is_field_named(field: &syn::Field, name: &str) -> bool {
+    field
+        .ident
+        .as_ref()
+        .is_some_and(|ident| ident.to_string().trim_start_matches("r#") == name)
+}
+
+/// Error unless placeholders and positional fields pair up one to one.
+fn check_field_count(
+    field_count: usize,
+    placeholder_count: usize,
+    fields: &Fields,
+    format_lit: &LitStr,
+) -> Result<(), TokenStream> {
+    if placeholder_count > field_count {
+        return Err(make_error(
+            format_lit.span(),
+            format!(
+                "Format string has {} placeholder(s) but there are only {} field(s)",
+                placeholder_count, field_count
+            ),
+        ));
+    }
+    if let Some(field) = fields.iter().nth(placeholder_count) {
+        return Err(make_error(
+            field.span(),
+            format!(
+                "Field {} is never captured by the format string {:?}",
+                placeholder_count,
+                format_lit.value()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Compile error at `span`.
+fn make_error(span: Span, message: String) -> TokenStream {
+    syn::Error::new(span, message).to_compile_error().into()
+}
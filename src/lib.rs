@@ -1,14 +1,23 @@
 //! C-style scanf/sscanf procedural macros for text parsing.
 //!
 //! - `scanf!`: Read and parse from stdin
+//! - `fscanf!`: Read and parse a line from any `BufRead`
+//! - `scan_lines!`: Iterate over the parsed lines of a `BufRead`
+//! - `ascanf!` / `ascan_lines!`: Async versions over `tokio::io::AsyncBufRead` (`tokio` feature)
 //! - `sscanf!`: Parse from string
+//! - `sscanf_ws!`: Parse from string with C `scanf` whitespace rules
+//! - `bscanf!`: Parse from bytes, without requiring UTF-8 input
+//! - `sscanf_let!`: Parse from string into new `let` bindings
+//! - `sscan!`: Parse from string, returning the values
+//! - `scanf_match!`: Match a string against several formats
+//! - `#[derive(Scanf)]`: Implement `FromStr` from a `#[scanf("...")]` format
 //!
 //! # Architecture
 //!
 //! Compile-time: `tokenization` → `codegen` → expansion
 //! Runtime: Generated code parses input with `.find()` and `.parse()`
 //!
-//! Modules: `constants`, `types`, `validation`, `parsing`, `tokenization`, `codegen`
+//! Modules: `constants`, `types`, `validation`, `parsing`, `tokenization`, `codegen`, `derive`
 //!
 //! # Hygiene
 //!
@@ -16,8 +25,13 @@
 //!
 //! # Limitations
 //!
-//! - Consecutive placeholders `{}{}` not allowed (ambiguous)
-//! - Greedy parsing (no backtracking)
+//! - Consecutive placeholders `{}{}` not allowed (ambiguous) unless the first has a width `{:4}{}`
+//!   or a character set `{:[0-9]}{}`
+//! - `[[` opens an optional section when a later `]]?` closes it; otherwise it is literal text
+//! - A leading flag group such as `(?i)` or `(?b)` is not literal text; case-insensitive
+//!   matching covers ASCII only
+//! - Greedy parsing: a field ends at the first occurrence of the text after it, unless `(?b)`
+//!   enables backtracking (retries re-scan at most 1 MiB of input in total)
 //! - Types must implement `FromStr`
 //! - `scanf!` trims trailing newlines
 //!
@@ -32,6 +46,7 @@
 
 mod codegen;
 mod constants;
+mod derive;
 mod parsing;
 mod tokenization;
 mod types;
@@ -39,16 +54,44 @@ mod validation;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::parse_macro_input;
+use syn::{DeriveInput, Lit, Pat, parse_macro_input, spanned::Spanned};
 
-use codegen::generate_scanf_implementation;
-use parsing::{ScanfArgs, SscanfArgs};
+use codegen::{
+    Input, MatchOptions, check_named_slots, generate_capture_implementation,
+    generate_collected_result, generate_returned_values, generate_scanf_implementation,
+};
+use derive::expand_derive_scanf;
+use parsing::{BscanfArgs, ExplicitArgs, ScanfArgs, ScanfMatchArgs, SscanfArgs, SscanfLetArgs};
 
 /// Parse a string with a format string, similar to C's `sscanf`.
 ///
-/// Syntax: `sscanf!(input, "format", args...)`
+/// Syntax: `sscanf!(input, "format", args...)`, where args are `&mut` places, then
+/// `name = &mut place` ones as in `format!`.
 ///
-/// Placeholders: `{name}` captures to variable, `{}` needs `&mut arg`
+/// Placeholders:
+/// - `{name}` captures to the named argument or variable, or to a place like `{point.x}` or
+///   `{arr[0]}`.
+/// - `{}` needs a `&mut arg`; `{0}` / `{1}` use the argument at that position as in `format!`
+///   (a repeated position must match the same value again).
+/// - `{:8}` / `{name:8}` read a fixed width.
+/// - `{:[a-z]}` / `{:[^,]}` read the longest run of chars in (or not in) a set.
+/// - `{_}` / `{*}` skip a field; `{_:i32}` still checks it parses.
+/// - `{:x}` / `{:o}` / `{:b}` parse an integer radix (`{:#x}` requires `0x`); `{:i}` takes the
+///   radix from the prefix like C's `%i`.
+/// - `{:/regex/}` matches a regex (`regex` feature).
+/// - `{nums*,}` / `{nums:Vec<u32>;sep=","}` capture a separated list into a `Vec`.
+/// - `{method:(GET|POST)}` captures one of several literal alternatives, and like text it also
+///   ends the field before it.
+/// - `{path:last}` ends at the last occurrence of the text after it (up to the next literal
+///   text).
+/// - `{:q}` reads a `"..."` or `'...'` string and parses its contents, with `\` escapes and
+///   doubled quotes resolved.
+///
+/// `[[...]]?` is an optional section: if it does not match, input and its captures are left as
+/// they were.
+/// A leading `(?i)` makes literal text match ignoring ASCII case; captures keep their case.
+/// A leading `(?b)` retries fields at later occurrences of the text after them when the parse
+/// fails (fields inside `[[...]]?` are not retried); flags combine as `(?ib)`.
 ///
 /// Returns `io::Result<()>`. Types must implement `FromStr`.
 ///
@@ -79,9 +122,13 @@ pub fn sscanf(input: TokenStream) -> TokenStream {
 
     let input_expr = &args.input;
     let format_lit = &args.format;
-    let explicit_args: Vec<_> = args.args.iter().collect();
 
-    let generated = match generate_scanf_implementation(format_lit, &explicit_args) {
+    let generated = match generate_scanf_implementation(
+        format_lit,
+        &args.args,
+        Input::Str,
+        MatchOptions::default(),
+    ) {
         Ok(code) => code,
         Err(err) => return err,
     };
@@ -97,6 +144,395 @@ pub fn sscanf(input: TokenStream) -> TokenStream {
     TokenStream::from(expanded)
 }
 
+/// `sscanf!` with C `scanf` whitespace rules.
+///
+/// Syntax: `sscanf_ws!(input, "format", args...)`
+///
+/// Any whitespace in the format's literal text matches any run of whitespace, including none,
+/// and fields skip leading whitespace (character sets `{:[...]}` excepted, as with C's `%[`).
+/// A field followed by whitespace in the format ends at the first whitespace, like `%s`;
+/// trailing whitespace after a final field is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::sscanf_ws;
+///
+/// let mut a: i32 = 0;
+/// let mut b: i32 = 0;
+/// let mut op: String = String::new();
+/// sscanf_ws!("  10   +  20 ", "{a} {op} {b}").unwrap();
+/// assert_eq!((a, op.as_str(), b), (10, "+", 20));
+/// sscanf_ws!("3,4", "{a} , {b}").unwrap();
+/// assert_eq!((a, b), (3, 4));
+/// ```
+#[proc_macro]
+pub fn sscanf_ws(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let input_expr = &args.input;
+    let format_lit = &args.format;
+
+    let options = MatchOptions {
+        c_whitespace: true,
+        ..MatchOptions::default()
+    };
+    let generated = match generate_scanf_implementation(format_lit, &args.args, Input::Str, options)
+    {
+        Ok(code) => code,
+        Err(err) => return err,
+    };
+
+    let expanded = quote! {{
+        let mut result: std::io::Result<()> = Ok(());
+        let mut remaining = #input_expr;
+        #(#generated)*
+        result
+    }};
+
+    TokenStream::from(expanded)
+}
+
+/// Parse a byte slice with a byte string format, like `sscanf!` without requiring UTF-8.
+///
+/// Syntax: `bscanf!(bytes, b"format", args...)`
+///
+/// Literal text is matched byte for byte and widths count bytes. Each field is checked
+/// to be UTF-8 only when parsed with `FromStr`; annotate a field `{name:&[u8]}` to
+/// borrow its raw bytes instead. The format itself must be valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::bscanf;
+///
+/// // Latin-1 "café" is not valid UTF-8
+/// let input: &[u8] = b"caf\xe9:42";
+/// let mut word: &[u8] = &[];
+/// let mut number: u32 = 0;
+/// bscanf!(input, b"{word:&[u8]}:{number}").unwrap();
+/// assert_eq!(word, b"caf\xe9");
+/// assert_eq!(number, 42);
+/// ```
+#[proc_macro]
+pub fn bscanf(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as BscanfArgs);
+
+    let input_expr = &args.input;
+
+    let format_lit = match String::from_utf8(args.format.value()) {
+        Ok(format_str) => syn::LitStr::new(&format_str, args.format.span()),
+        Err(_) => {
+            return syn::Error::new(
+                args.format.span(),
+                "bscanf! format must be valid UTF-8; only the input may contain arbitrary bytes",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let generated = match generate_scanf_implementation(
+        &format_lit,
+        &args.args,
+        Input::Bytes,
+        MatchOptions::default(),
+    ) {
+        Ok(code) => code,
+        Err(err) => return err,
+    };
+
+    // Scope isolation ensures macro hygiene
+    let expanded = quote! {{
+        let mut result: std::io::Result<()> = Ok(());
+        let mut remaining: &[u8] = #input_expr;
+        #(#generated)*
+        result
+    }};
+
+    TokenStream::from(expanded)
+}
+
+/// Parse a string and declare the captured values as new variables, like `let ... else`.
+///
+/// Syntax: `sscanf_let!(input, "format" else { diverge });`
+///
+/// Placeholders: `{name}` or `{name:Type}` introduces `name` in the caller's scope.
+/// Bindings exist only if the whole input parses; otherwise the `else` block runs and
+/// must diverge (`return`, `break`, `continue`, `panic!`...). Without an inline type,
+/// the type is inferred from how the binding is used.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::sscanf_let;
+///
+/// let input = "42,hello";
+/// sscanf_let!(input, "{x:i32},{name:String}" else {
+///     panic!("invalid input");
+/// });
+/// assert_eq!(x, 42);
+/// assert_eq!(name, "hello");
+/// ```
+#[proc_macro]
+pub fn sscanf_let(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfLetArgs);
+
+    let input_expr = &args.input;
+    let format_lit = &args.format;
+    let else_block = &args.else_block;
+
+    let (generated, slots) =
+        match generate_capture_implementation(format_lit, MatchOptions::default()) {
+            Ok(code) => code,
+            Err(err) => return err,
+        };
+    if let Err(err) = check_named_slots(&slots, format_lit, "sscanf_let!") {
+        return err;
+    }
+
+    let bindings = slots
+        .iter()
+        .filter_map(|slot| slot.name.as_deref())
+        .map(|name| syn::Ident::new(name, proc_macro2::Span::call_site()));
+    let collected = generate_collected_result(&slots);
+
+    let expanded = quote! {
+        let Ok((#(#bindings,)*)) = ({
+            let mut result: std::io::Result<()> = Ok(());
+            let mut remaining = #input_expr;
+            #(#generated)*
+            #collected
+        }) else #else_block;
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Parse a string and return the captured values instead of writing through `&mut`.
+///
+/// Syntax: `sscan!(input, "format")`
+///
+/// Returns `io::Result<T>` for one placeholder and `io::Result<(T1, T2, ...)>` for several,
+/// in format order (named and anonymous alike). Types are inferred from how the result is
+/// used, or given inline with `{:Type}`; no `Default` value is needed.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::sscan;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let (w, h): (u32, u32) = sscan!("1920x1080", "{}x{}")?;
+/// assert_eq!((w, h), (1920, 1080));
+///
+/// let port = sscan!("port=8080", "port={:u16}")?;
+/// assert_eq!(port, 8080);
+/// # Ok(())
+/// # }
+/// ```
+#[proc_macro]
+pub fn sscan(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let input_expr = &args.input;
+    let format_lit = &args.format;
+
+    if let Err(err) = reject_explicit_args(&args.args, "sscan!") {
+        return err;
+    }
+
+    let (generated, slots) =
+        match generate_capture_implementation(format_lit, MatchOptions::default()) {
+            Ok(code) => code,
+            Err(err) => return err,
+        };
+    let values = generate_returned_values(&slots);
+
+    // Scope isolation ensures macro hygiene
+    let expanded = quote! {{
+        let mut result: std::io::Result<()> = Ok(());
+        let mut remaining = #input_expr;
+        #(#generated)*
+        #values
+    }};
+
+    TokenStream::from(expanded)
+}
+
+/// Match a string against several format strings, like a `match` expression.
+///
+/// Syntax: `scanf_match!(input, { "format" => expr, ..., _ => expr })`
+///
+/// Arms are tried in order. The first format matching the whole input (and whose optional
+/// `if` guard holds) runs with its named placeholders bound as new variables; `{name:Type}`
+/// annotates a binding. Nothing is assigned for arms that fail, so partial matches never
+/// leak into later arms. A final `_` arm is required.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::scanf_match;
+///
+/// fn describe(line: &str) -> String {
+///     scanf_match!(line, {
+///         "GET {path:String}" => format!("get {}", path),
+///         "PUT {path:String} {len:usize}" if len > 0 => format!("put {} bytes to {}", len, path),
+///         _ => "unknown".to_string(),
+///     })
+/// }
+///
+/// assert_eq!(describe("GET /index.html"), "get /index.html");
+/// assert_eq!(describe("PUT /upload 42"), "put 42 bytes to /upload");
+/// assert_eq!(describe("PUT /upload 0"), "unknown");
+/// ```
+#[proc_macro]
+pub fn scanf_match(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as ScanfMatchArgs);
+
+    let input_expr = &args.input;
+    let input_ident = syn::Ident::new("input", proc_macro2::Span::mixed_site());
+    let label = syn::Lifetime::new("'scanf_match", proc_macro2::Span::mixed_site());
+
+    let Some((fallback, format_arms)) = args.arms.split_last() else {
+        return syn::Error::new(
+            input_expr.span(),
+            "scanf_match! needs at least a final `_ => ...` arm",
+        )
+        .to_compile_error()
+        .into();
+    };
+    if !matches!(fallback.pat, Pat::Wild(_)) || fallback.guard.is_some() {
+        return syn::Error::new(
+            fallback.pat.span(),
+            "The last scanf_match! arm must be an unguarded `_ => ...` fallback",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let mut attempts = Vec::with_capacity(format_arms.len());
+    for arm in format_arms {
+        let format_lit = match &arm.pat {
+            Pat::Lit(pat) => match &pat.lit {
+                Lit::Str(format_lit) => format_lit,
+                other => {
+                    return syn::Error::new(other.span(), "Expected a format string literal")
+                        .to_compile_error()
+                        .into();
+                }
+            },
+            Pat::Wild(wild) => {
+                return syn::Error::new(
+                    wild.span(),
+                    "`_` must be the last scanf_match! arm; arms after it are unreachable",
+                )
+                .to_compile_error()
+                .into();
+            }
+            other => {
+                return syn::Error::new(other.span(), "Expected a format string literal")
+                    .to_compile_error()
+                    .into();
+            }
+        };
+
+        let options = MatchOptions {
+            whole_input: true,
+            ..MatchOptions::default()
+        };
+        let (generated, slots) = match generate_capture_implementation(format_lit, options) {
+            Ok(code) => code,
+            Err(err) => return err,
+        };
+        if let Err(err) = check_named_slots(&slots, format_lit, "scanf_match!") {
+            return err;
+        }
+
+        let bindings = slots
+            .iter()
+            .filter_map(|slot| slot.name.as_deref())
+            .map(|name| syn::Ident::new(name, proc_macro2::Span::call_site()));
+        let collected = generate_collected_result(&slots);
+        let body = &arm.body;
+        let run = match &arm.guard {
+            Some((_, condition)) => quote! {
+                if #condition {
+                    break #label { #body };
+                }
+            },
+            None => quote! { break #label { #body }; },
+        };
+
+        attempts.push(quote! {
+            if let Ok((#(#bindings,)*)) = {
+                let mut result: std::io::Result<()> = Ok(());
+                let mut remaining: &str = #input_ident;
+                #(#generated)*
+                #collected
+            } {
+                #run
+            }
+        });
+    }
+
+    let fallback_body = &fallback.body;
+    let expanded = quote! {{
+        let #input_ident: &str = &#input_expr;
+        #label: {
+            #(#attempts)*
+            #fallback_body
+        }
+    }};
+
+    TokenStream::from(expanded)
+}
+
+/// Derive `FromStr` from a `#[scanf("format")]` attribute.
+///
+/// Named placeholders fill the fields with the same name; tuple structs take `{}` in order.
+/// Every field must be captured exactly once and the whole input must match.
+/// `Err` is `std::io::Error`, as for the other macros.
+///
+/// On enums every variant has its own `#[scanf("...")]`; variants are tried in declaration
+/// order and the first one matching the whole input is returned.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::Scanf;
+///
+/// #[derive(Scanf, Debug, PartialEq)]
+/// #[scanf("({x},{y})")]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// let point: Point = "(3,-4)".parse().unwrap();
+/// assert_eq!(point, Point { x: 3, y: -4 });
+///
+/// #[derive(Scanf, Debug, PartialEq)]
+/// enum Command {
+///     #[scanf("MOVE {x} {y}")]
+///     Move { x: i32, y: i32 },
+///     #[scanf("QUIT")]
+///     Quit,
+/// }
+///
+/// assert_eq!("MOVE 1 -2".parse::<Command>().unwrap(), Command::Move { x: 1, y: -2 });
+/// assert_eq!("QUIT".parse::<Command>().unwrap(), Command::Quit);
+/// ```
+#[proc_macro_derive(Scanf, attributes(scanf))]
+pub fn derive_scanf(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand_derive_scanf(&input) {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(err) => err,
+    }
+}
+
 /// Read from stdin and parse with a format string, similar to C's `scanf`.
 ///
 /// Syntax: `scanf!("format", args...)`
@@ -123,9 +559,13 @@ pub fn sscanf(input: TokenStream) -> TokenStream {
 pub fn scanf(input: TokenStream) -> TokenStream {
     let args = parse_macro_input!(input as ScanfArgs);
     let format_lit = &args.format;
-    let explicit_args: Vec<_> = args.args.iter().collect();
 
-    let generated = match generate_scanf_implementation(format_lit, &explicit_args) {
+    let generated = match generate_scanf_implementation(
+        format_lit,
+        &args.args,
+        Input::Str,
+        MatchOptions::default(),
+    ) {
         Ok(code) => code,
         Err(err) => return err,
     };
@@ -147,3 +587,320 @@ pub fn scanf(input: TokenStream) -> TokenStream {
     }};
     TokenStream::from(expanded)
 }
+
+/// Read one line from any `BufRead` and parse it with a format string, like C's `fscanf`.
+///
+/// Syntax: `fscanf!(reader, "format", args...)` with `reader: &mut impl BufRead`
+///
+/// Same placeholders and newline trimming as `scanf!`. Returns `io::Result<()>`; reaching
+/// end of input before any byte is read gives an `ErrorKind::UnexpectedEof` error.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::fscanf;
+/// use std::io::Cursor;
+///
+/// let mut reader = Cursor::new("3 4\n5 6\n");
+/// let mut a: i32 = 0;
+/// let mut b: i32 = 0;
+/// fscanf!(&mut reader, "{a} {b}").unwrap();
+/// assert_eq!((a, b), (3, 4));
+/// fscanf!(&mut reader, "{} {}", &mut a, &mut b).unwrap();
+/// assert_eq!((a, b), (5, 6));
+///
+/// let error = fscanf!(&mut reader, "{a} {b}").unwrap_err();
+/// assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+/// ```
+#[proc_macro]
+pub fn fscanf(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let reader_expr = &args.input;
+    let format_lit = &args.format;
+
+    let generated = match generate_scanf_implementation(
+        format_lit,
+        &args.args,
+        Input::Str,
+        MatchOptions::default(),
+    ) {
+        Ok(code) => code,
+        Err(err) => return err,
+    };
+
+    let read_line = quote! { std::io::BufRead::read_line(&mut *#reader_expr, &mut buffer) };
+    let expanded = generate_read_and_parse(&read_line, &generated);
+    TokenStream::from(expanded)
+}
+
+/// Iterate over the lines of a `BufRead`, parsing each one with a format string.
+///
+/// Syntax: `scan_lines!(reader, "format")`
+///
+/// Yields `io::Result<T>` (one placeholder) or `io::Result<(T1, T2, ...)>` per line, typed
+/// like `sscan!`; use `{:MyStruct}` with `#[derive(Scanf)]` to get records. Errors are
+/// prefixed with the 1-based line number. One line buffer is reused for the whole input;
+/// iteration ends at end of input or after a read error.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::scan_lines;
+/// use std::io::Cursor;
+///
+/// let log = Cursor::new("10 INFO started\n11 WARN disk low\n");
+/// let mut records = scan_lines!(log, "{ts} {level} {msg}");
+/// let (ts, level, msg): (u64, String, String) = records.next().unwrap().unwrap();
+/// assert_eq!((ts, level.as_str(), msg.as_str()), (10, "INFO", "started"));
+/// assert!(records.next().unwrap().is_ok());
+/// assert!(records.next().is_none());
+/// ```
+#[proc_macro]
+pub fn scan_lines(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let reader_expr = &args.input;
+    let format_lit = &args.format;
+
+    if let Err(err) = reject_explicit_args(&args.args, "scan_lines!") {
+        return err;
+    }
+
+    let (generated, slots) =
+        match generate_capture_implementation(format_lit, MatchOptions::default()) {
+            Ok(code) => code,
+            Err(err) => return err,
+        };
+    let values = generate_returned_values(&slots);
+
+    let expanded = quote! {{
+        let mut reader = #reader_expr;
+        let mut buffer = String::new();
+        let mut line_number: usize = 0;
+        let mut finished = false;
+        std::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            buffer.clear();
+            line_number += 1;
+            let parsed = match std::io::BufRead::read_line(&mut reader, &mut buffer) {
+                Ok(0) => {
+                    finished = true;
+                    return None;
+                }
+                Ok(_) => {
+                    let input = buffer.trim_end_matches('\n').trim_end_matches('\r');
+                    let mut result: std::io::Result<()> = Ok(());
+                    let mut remaining: &str = input;
+                    #(#generated)*
+                    #values
+                }
+                Err(e) => {
+                    finished = true;
+                    Err(e)
+                }
+            };
+            Some(parsed.map_err(|error| {
+                std::io::Error::new(error.kind(), format!("Line {}: {}", line_number, error))
+            }))
+        })
+    }};
+    TokenStream::from(expanded)
+}
+
+/// Error for value-returning macros given `&mut` arguments after the format string.
+fn reject_explicit_args(args: &ExplicitArgs, macro_name: &str) -> Result<(), TokenStream> {
+    match args.first_span() {
+        Some(span) => Err(syn::Error::new(
+            span,
+            format!(
+                "{} returns the captured values and takes no arguments after the format string. \
+                 Use sscanf! to write through '&mut' arguments",
+                macro_name
+            ),
+        )
+        .to_compile_error()
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Read one line with `read_line` (a call filling `buffer`) and run the parsing code on it.
+///
+/// Shared by `fscanf!` and `ascanf!`: only the line acquisition differs.
+fn generate_read_and_parse(
+    read_line: &proc_macro2::TokenStream,
+    generated: &[proc_macro2::TokenStream],
+) -> proc_macro2::TokenStream {
+    // Scope isolation ensures macro hygiene
+    quote! {{
+        let mut result: std::io::Result<()> = Ok(());
+        let mut buffer = String::new();
+        match #read_line {
+            Ok(0) => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "End of input reached before a line could be read"
+            )),
+            Ok(_) => {
+                let input = buffer.trim_end_matches('\n').trim_end_matches('\r');
+                let mut remaining: &str = input;
+                #(#generated)*
+                result
+            }
+            Err(e) => Err(e)
+        }
+    }}
+}
+
+/// Async `fscanf!`: read one line from a `tokio::io::AsyncBufRead` and parse it.
+///
+/// Syntax: `ascanf!(reader, "format", args...).await` with
+/// `reader: &mut (impl AsyncBufRead + Unpin)`
+///
+/// Evaluates to a future of `io::Result<()>`, with the same placeholders, newline trimming
+/// and `UnexpectedEof` reporting as `fscanf!`. Requires the `tokio` feature; the calling
+/// crate must depend on `tokio` with `io-util`.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::ascanf;
+/// use tokio::io::BufReader;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut reader = BufReader::new("3 4\n".as_bytes());
+/// let mut a: i32 = 0;
+/// let mut b: i32 = 0;
+/// ascanf!(&mut reader, "{a} {b}").await.unwrap();
+/// assert_eq!((a, b), (3, 4));
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[proc_macro]
+pub fn ascanf(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let reader_expr = &args.input;
+    let format_lit = &args.format;
+
+    let generated = match generate_scanf_implementation(
+        format_lit,
+        &args.args,
+        Input::Str,
+        MatchOptions::default(),
+    ) {
+        Ok(code) => code,
+        Err(err) => return err,
+    };
+
+    let read_line = quote! {
+        ::tokio::io::AsyncBufReadExt::read_line(&mut *#reader_expr, &mut buffer).await
+    };
+    let read_and_parse = generate_read_and_parse(&read_line, &generated);
+    TokenStream::from(quote! { async #read_and_parse })
+}
+
+/// Async `scan_lines!`: parse every line of a `tokio::io::AsyncBufRead`.
+///
+/// Syntax: `ascan_lines!(reader, "format")`, then `.next_line().await` until `None`
+///
+/// Each call yields `Option<io::Result<T>>` typed like `sscan!`, with line-numbered errors
+/// and one reused line buffer. Requires the `tokio` feature; the calling crate must depend
+/// on `tokio` with `io-util`.
+///
+/// # Examples
+///
+/// ```
+/// use scanf::ascan_lines;
+/// use tokio::io::BufReader;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let reader = BufReader::new("GET /\nPUT /upload\n".as_bytes());
+/// let mut requests = ascan_lines!(reader, "{method} {path}");
+/// while let Some(request) = requests.next_line().await {
+///     let (method, path): (String, String) = request.unwrap();
+///     println!("{method} {path}");
+/// }
+/// # }
+/// ```
+#[cfg(feature = "tokio")]
+#[proc_macro]
+pub fn ascan_lines(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as SscanfArgs);
+
+    let reader_expr = &args.input;
+    let format_lit = &args.format;
+
+    if let Err(err) = reject_explicit_args(&args.args, "ascan_lines!") {
+        return err;
+    }
+
+    let (generated, slots) =
+        match generate_capture_implementation(format_lit, MatchOptions::default()) {
+            Ok(code) => code,
+            Err(err) => return err,
+        };
+    let values = generate_returned_values(&slots);
+
+    let expanded = quote! {{
+        struct AsyncScanLines<R, F> {
+            reader: R,
+            buffer: String,
+            line_number: usize,
+            finished: bool,
+            parse: F,
+        }
+
+        impl<R, F, T> AsyncScanLines<R, F>
+        where
+            R: ::tokio::io::AsyncBufRead + Unpin,
+            F: FnMut(&str) -> std::io::Result<T>,
+        {
+            async fn next_line(&mut self) -> Option<std::io::Result<T>> {
+                if self.finished {
+                    return None;
+                }
+                self.buffer.clear();
+                self.line_number += 1;
+                let parsed = match ::tokio::io::AsyncBufReadExt::read_line(
+                    &mut self.reader,
+                    &mut self.buffer,
+                )
+                .await
+                {
+                    Ok(0) => {
+                        self.finished = true;
+                        return None;
+                    }
+                    Ok(_) => (self.parse)(self.buffer.trim_end_matches('\n').trim_end_matches('\r')),
+                    Err(e) => {
+                        self.finished = true;
+                        Err(e)
+                    }
+                };
+                let line_number = self.line_number;
+                Some(parsed.map_err(|error| {
+                    std::io::Error::new(error.kind(), format!("Line {}: {}", line_number, error))
+                }))
+            }
+        }
+
+        AsyncScanLines {
+            reader: #reader_expr,
+            buffer: String::new(),
+            line_number: 0,
+            finished: false,
+            parse: |input: &str| {
+                let mut result: std::io::Result<()> = Ok(());
+                let mut remaining: &str = input;
+                #(#generated)*
+                #values
+            },
+        }
+    }};
+    TokenStream::from(expanded)
+}
@@ -1,17 +1,79 @@
 //! Macro argument parsing structures.
 
+use proc_macro2::Span;
 use syn::{
-    Expr, LitStr, Token,
+    Arm, Block, Expr, Ident, LitByteStr, LitStr, Token, braced,
     parse::{Parse, ParseStream},
-    punctuated::Punctuated,
-    token::Comma,
+    spanned::Spanned,
 };
 
-/// sscanf! arguments: input, format, args
+/// Arguments after the format string: `&mut x` positional ones, then `name = &mut x` named
+/// ones as in `format!`.
+#[derive(Default)]
+pub struct ExplicitArgs {
+    /// Bound to `{}` and `{N}`.
+    pub positional: Vec<Expr>,
+    /// Bound to `{name}` instead of a variable in scope.
+    pub named: Vec<(Ident, Expr)>,
+}
+
+impl ExplicitArgs {
+    /// Parse `, arg, ...` after the format string (nothing when the input ends there).
+    ///
+    /// Errors on duplicate names and on positional arguments after named ones.
+    fn parse_after_format(input: ParseStream) -> syn::Result<Self> {
+        let mut args = ExplicitArgs::default();
+        if input.is_empty() {
+            return Ok(args);
+        }
+        input.parse::<Token![,]>()?;
+
+        while !input.is_empty() {
+            if input.peek(Ident) && input.peek2(Token![=]) {
+                let name: Ident = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if args.named.iter().any(|(other, _)| *other == name) {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!("Duplicate argument named '{}'", name),
+                    ));
+                }
+                args.named.push((name, input.parse()?));
+            } else {
+                let expr: Expr = input.parse()?;
+                if !args.named.is_empty() {
+                    return Err(syn::Error::new(
+                        expr.span(),
+                        "Positional arguments cannot follow named arguments",
+                    ));
+                }
+                args.positional.push(expr);
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(args)
+    }
+
+    /// Span of the first argument, `None` without arguments.
+    pub fn first_span(&self) -> Option<Span> {
+        match (self.positional.first(), self.named.first()) {
+            (Some(expr), _) => Some(expr.span()),
+            (None, Some((name, _))) => Some(name.span()),
+            (None, None) => None,
+        }
+    }
+}
+
+/// sscanf!/fscanf! arguments: input (string or reader), format, args
 pub struct SscanfArgs {
     pub input: Expr,
     pub format: LitStr,
-    pub args: Punctuated<Expr, Comma>,
+    pub args: ExplicitArgs,
 }
 
 impl Parse for SscanfArgs {
@@ -20,12 +82,7 @@ impl Parse for SscanfArgs {
         input.parse::<Token![,]>()?;
         let format = input.parse()?;
 
-        let args = if input.is_empty() {
-            Punctuated::new()
-        } else {
-            input.parse::<Token![,]>()?;
-            Punctuated::parse_terminated(input)?
-        };
+        let args = ExplicitArgs::parse_after_format(input)?;
 
         Ok(SscanfArgs {
             input: input_expr,
@@ -38,20 +95,90 @@ impl Parse for SscanfArgs {
 /// scanf! arguments: format, args
 pub struct ScanfArgs {
     pub format: LitStr,
-    pub args: Punctuated<Expr, Comma>,
+    pub args: ExplicitArgs,
 }
 
 impl Parse for ScanfArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let format: LitStr = input.parse()?;
 
-        let args = if input.is_empty() {
-            Punctuated::new()
-        } else {
-            input.parse::<Token![,]>()?;
-            Punctuated::parse_terminated(input)?
-        };
+        let args = ExplicitArgs::parse_after_format(input)?;
 
         Ok(Self { format, args })
     }
 }
+
+/// sscanf_let! arguments: input, format, else block
+pub struct SscanfLetArgs {
+    pub input: Expr,
+    pub format: LitStr,
+    pub else_block: Block,
+}
+
+impl Parse for SscanfLetArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let input_expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let format = input.parse()?;
+        input.parse::<Token![else]>()?;
+        let else_block = input.parse()?;
+
+        Ok(SscanfLetArgs {
+            input: input_expr,
+            format,
+            else_block,
+        })
+    }
+}
+
+/// scanf_match! arguments: input, `{ "format" => expr, ..., _ => expr }`
+pub struct ScanfMatchArgs {
+    pub input: Expr,
+    pub arms: Vec<Arm>,
+}
+
+impl Parse for ScanfMatchArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let input_expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let content;
+        braced!(content in input);
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            arms.push(content.call(Arm::parse)?);
+        }
+
+        if !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(ScanfMatchArgs {
+            input: input_expr,
+            arms,
+        })
+    }
+}
+
+/// bscanf! arguments: input bytes, byte string format, args
+pub struct BscanfArgs {
+    pub input: Expr,
+    pub format: LitByteStr,
+    pub args: ExplicitArgs,
+}
+
+impl Parse for BscanfArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let input_expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let format = input.parse()?;
+
+        let args = ExplicitArgs::parse_after_format(input)?;
+
+        Ok(BscanfArgs {
+            input: input_expr,
+            format,
+            args,
+        })
+    }
+}
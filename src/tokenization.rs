@@ -1,17 +1,20 @@
 //! Format string tokenization at compile-time.
 
 use crate::constants::{
-    IDENTIFIER_CAPACITY, MAX_FORMAT_STRING_LEN, MAX_IDENTIFIER_LEN, MAX_TOKENS,
-    TEXT_SEGMENT_CAPACITY, TOKENS_INITIAL_CAPACITY,
+    IDENTIFIER_CAPACITY, MAX_FORMAT_STRING_LEN, MAX_IDENTIFIER_LEN, MAX_PLACEHOLDER_LEN,
+    MAX_TOKENS, TEXT_SEGMENT_CAPACITY, TOKENS_INITIAL_CAPACITY,
 };
-use crate::types::{FormatToken, Placeholder};
-use crate::validation::is_valid_identifier;
+use crate::types::{CharClass, FormatSpec, FormatToken, Placeholder, Radix};
+use crate::validation::is_valid_field_path;
 use proc_macro::TokenStream;
+use std::iter::Peekable;
+use std::str::Chars;
 use syn::LitStr;
 
-/// Tokenize format string into text/placeholders. Handles `{{`/`}}` escapes.
+/// Tokenize format string into text/placeholders. Handles `{{`/`}}` escapes, `{name:spec}`
+/// and nested `[[...]]?` optional sections; a `[[` or `]]?` without its pair is literal text.
 ///
-/// Security: enforces MAX_FORMAT_STRING_LEN, MAX_TOKENS, MAX_IDENTIFIER_LEN limits.
+/// Security: enforces MAX_FORMAT_STRING_LEN, MAX_TOKENS, MAX_PLACEHOLDER_LEN, MAX_IDENTIFIER_LEN limits.
 pub fn tokenize_format_string(
     format_str: &str,
     format_lit: &LitStr,
@@ -31,24 +34,32 @@ pub fn tokenize_format_string(
     }
 
     let mut tokens: Vec<FormatToken> = Vec::with_capacity(TOKENS_INITIAL_CAPACITY);
+    // Enclosing sequences of the `[[...]]?` sections being read
+    let mut outer_sequences: Vec<Vec<FormatToken>> = Vec::new();
+    // Tokens at every nesting level, for MAX_TOKENS
+    let mut token_count: usize = 0;
+    // `[[` openers read so far, and those no `]]?` closes
+    let mut openers: usize = 0;
+    let unclosed = unclosed_sections(format_str);
     let mut chars = format_str.chars().peekable();
     let mut current_text = String::with_capacity(TEXT_SEGMENT_CAPACITY);
 
-    let push_token =
+    let mut push_token =
         |tokens: &mut Vec<FormatToken>, token: FormatToken| -> Result<(), TokenStream> {
-            if tokens.len() >= MAX_TOKENS {
+            if token_count >= MAX_TOKENS {
                 return Err(syn::Error::new(
                     format_lit.span(),
                     format!(
                         "Too many tokens in format string (would exceed {}). Maximum allowed: {}. \
                      This limit prevents compile-time resource exhaustion.",
-                        tokens.len() + 1,
+                        token_count + 1,
                         MAX_TOKENS
                     ),
                 )
                 .to_compile_error()
                 .into());
             }
+            token_count += 1;
             tokens.push(token);
             Ok(())
         };
@@ -70,52 +81,17 @@ pub fn tokenize_format_string(
                     current_text = String::with_capacity(TEXT_SEGMENT_CAPACITY);
                 }
 
-                let mut content = String::with_capacity(IDENTIFIER_CAPACITY);
-                for c2 in chars.by_ref() {
-                    if c2 == '}' {
-                        break;
-                    }
-
-                    if content.len() >= MAX_IDENTIFIER_LEN {
-                        return Err(syn::Error::new(
-                            format_lit.span(),
-                            format!(
-                                "Identifier in placeholder too long (>{} characters). \
-                                 This limit prevents compile-time DoS attacks.",
-                                MAX_IDENTIFIER_LEN
-                            ),
-                        )
-                        .to_compile_error()
-                        .into());
-                    }
-
-                    content.push(c2);
-                }
+                let content = read_placeholder_content(&mut chars)
+                    .map_err(|message| make_format_error(format_lit, message))?;
 
-                if content.is_empty() {
-                    push_token(
-                        &mut tokens,
-                        FormatToken::Placeholder(Placeholder::Anonymous),
-                    )?;
-                } else if is_valid_identifier(&content) {
-                    push_token(
-                        &mut tokens,
-                        FormatToken::Placeholder(Placeholder::Named(content.into_boxed_str())),
-                    )?;
+                let placeholder = parse_placeholder(&content)
+                    .map_err(|message| make_format_error(format_lit, message))?;
+                let token = if placeholder.spec().alternatives.is_some() {
+                    FormatToken::Alternation(placeholder)
                 } else {
-                    return Err(syn::Error::new(
-                        format_lit.span(),
-                        format!(
-                            "Invalid identifier '{}' in placeholder. \
-                             Identifiers must start with a letter or underscore, \
-                             contain only alphanumeric characters or underscores, \
-                             and not be Rust keywords. Use '{{}}' for anonymous placeholders.",
-                            content
-                        ),
-                    )
-                    .to_compile_error()
-                    .into());
-                }
+                    FormatToken::Placeholder(placeholder)
+                };
+                push_token(&mut tokens, token)?;
             }
             '}' => {
                 if chars.peek() == Some(&'}') {
@@ -130,6 +106,40 @@ pub fn tokenize_format_string(
                     .into());
                 }
             }
+            '[' if chars.peek() == Some(&'[') => {
+                chars.next();
+                openers += 1;
+                if unclosed.contains(&(openers - 1)) {
+                    current_text.push_str("[[");
+                    continue;
+                }
+                if !current_text.is_empty() {
+                    push_token(
+                        &mut tokens,
+                        FormatToken::Text(std::mem::take(&mut current_text).into_boxed_str()),
+                    )?;
+                }
+                outer_sequences.push(std::mem::take(&mut tokens));
+            }
+            ']' if starts_section_end(&chars) && !outer_sequences.is_empty() => {
+                let outer = outer_sequences.pop().expect("a section is open");
+                chars.next();
+                chars.next();
+                if !current_text.is_empty() {
+                    push_token(
+                        &mut tokens,
+                        FormatToken::Text(std::mem::take(&mut current_text).into_boxed_str()),
+                    )?;
+                }
+                let section = std::mem::replace(&mut tokens, outer);
+                if section.is_empty() {
+                    return Err(make_format_error(
+                        format_lit,
+                        "Empty optional section '[[]]?' in format string".to_string(),
+                    ));
+                }
+                push_token(&mut tokens, FormatToken::Optional(section))?;
+            }
             other => current_text.push(other),
         }
     }
@@ -144,6 +154,533 @@ pub fn tokenize_format_string(
     Ok(tokens)
 }
 
+/// Ordinals of the `[[` openers in `format_str` that no later `]]?` closes.
+///
+/// Placeholders are skipped as the tokenizer reads them, so a `[[` inside `{...}` is not counted.
+fn unclosed_sections(format_str: &str) -> Vec<usize> {
+    let mut open: Vec<usize> = Vec::new();
+    let mut openers: usize = 0;
+    let mut chars = format_str.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '{' if read_placeholder_content(&mut chars).is_err() => break,
+            '[' if chars.peek() == Some(&'[') => {
+                chars.next();
+                open.push(openers);
+                openers += 1;
+            }
+            ']' if starts_section_end(&chars) && !open.is_empty() => {
+                chars.next();
+                chars.next();
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open
+}
+
+/// After a `]`, whether `]?` follows, closing an optional section.
+fn starts_section_end(chars: &Peekable<Chars>) -> bool {
+    let mut lookahead = chars.clone();
+    lookahead.next() == Some(']') && lookahead.next() == Some('?')
+}
+
+/// Read placeholder content up to its closing `}`.
+///
+/// Inside a `[...]` set, a `/.../` regex, a `(A|B)` group or a `sep="..."` string, `}` and
+/// `:` are content and `\` escapes the next char.
+fn read_placeholder_content(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    let mut content = String::with_capacity(IDENTIFIER_CAPACITY);
+    // Chars read since the opening `[`, `None` outside a set
+    let mut set_len: Option<usize> = None;
+    // Closing char of the regex, group or string being read
+    let mut closing: Option<char> = None;
+
+    while let Some(c) = chars.next() {
+        if content.len() >= MAX_PLACEHOLDER_LEN {
+            return Err(format!(
+                "Placeholder too long (>{} characters). \
+                 This limit prevents compile-time DoS attacks.",
+                MAX_PLACEHOLDER_LEN
+            ));
+        }
+
+        if let Some(end) = closing {
+            content.push(c);
+            match c {
+                '\\' => content.extend(chars.next()),
+                _ if c == end => closing = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (set_len, c) {
+            (None, '}') => return Ok(content),
+            (None, '"') if content.ends_with(";sep=") => closing = Some('"'),
+            (None, '[') => set_len = Some(0),
+            // A regex or group starts a spec item
+            (None, '/') if content.ends_with(':') => closing = Some('/'),
+            (None, '(') if content.ends_with(':') => closing = Some(')'),
+            (Some(_), '\\') => {
+                content.push(c);
+                if let Some(escaped) = chars.next() {
+                    content.push(escaped);
+                }
+                set_len = set_len.map(|len| len + 2);
+                continue;
+            }
+            // `]` right after `[` or `[^` is a literal member, as in C
+            (Some(len), ']') if len > 0 && !(len == 1 && content.ends_with('^')) => {
+                set_len = None;
+            }
+            (Some(len), _) => set_len = Some(len + 1),
+            (None, _) => {}
+        }
+        content.push(c);
+    }
+
+    match (set_len, closing) {
+        (Some(_), _) => Err("Unterminated character set '[' in placeholder".to_string()),
+        (None, Some('/')) => Err("Unterminated regex '/' in placeholder".to_string()),
+        (None, Some(')')) => Err("Unterminated group '(' in placeholder".to_string()),
+        (None, Some(_)) => Err("Unterminated separator string in placeholder".to_string()),
+        (None, None) => Ok(content),
+    }
+}
+
+/// Parse placeholder content `name:spec` (both parts optional).
+///
+/// Repeated captures give a separator as `name*SEP` or with a trailing `;sep="SEP"`.
+fn parse_placeholder(content: &str) -> Result<Placeholder, String> {
+    let (content, separator) = match content.find(";sep=\"") {
+        Some(index) => (
+            &content[..index],
+            Some(parse_separator(&content[index + 5..])?),
+        ),
+        None => (content, None),
+    };
+    let (name, mut spec) = match content.split_once(':') {
+        Some((name, spec)) => (name, parse_spec(spec)?),
+        None => (content, FormatSpec::default()),
+    };
+
+    let name = match name.split_once('*') {
+        Some((name, shorthand)) if !shorthand.is_empty() => {
+            if separator.is_some() {
+                return Err(format!(
+                    "Separator given twice in placeholder '{{{}}}'; use either '*SEP' or ';sep=\"SEP\"'",
+                    content
+                ));
+            }
+            spec.repeat = Some(shorthand.into());
+            name
+        }
+        _ => {
+            spec.repeat = separator;
+            name
+        }
+    };
+
+    if spec.repeat.is_some() && spec.alternatives.is_some() {
+        return Err(format!(
+            "Placeholder '{{{}}}' cannot both repeat and match alternatives",
+            content
+        ));
+    }
+
+    match name {
+        "" => return Ok(Placeholder::Anonymous(spec)),
+        "_" | "*" => return Ok(Placeholder::Discard(spec)),
+        _ if name.bytes().all(|byte| byte.is_ascii_digit()) => {
+            return match name.parse() {
+                Ok(index) => Ok(Placeholder::Indexed(index, spec)),
+                Err(_) => Err(format!(
+                    "Positional placeholder index '{}' is too large",
+                    name
+                )),
+            };
+        }
+        _ => {}
+    }
+
+    if name.chars().count() > MAX_IDENTIFIER_LEN {
+        return Err(format!(
+            "Identifier in placeholder too long (>{} characters). \
+             This limit prevents compile-time DoS attacks.",
+            MAX_IDENTIFIER_LEN
+        ));
+    }
+
+    if !is_valid_field_path(name) {
+        return Err(format!(
+            "Invalid identifier '{}' in placeholder. \
+             Identifiers must start with a letter or underscore, \
+             contain only alphanumeric characters or underscores, \
+             and not be Rust keywords; fields and indexes are written 'point.x' or 'arr[0]'. \
+             Use '{{}}' for anonymous placeholders.",
+            name
+        ));
+    }
+
+    Ok(Placeholder::Named(name.into(), spec))
+}
+
+/// Parse the quoted separator of `;sep="..."`, unescaping `\"` and `\\`.
+fn parse_separator(quoted: &str) -> Result<Box<str>, String> {
+    let body = quoted
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|body| !body.is_empty())
+        .ok_or_else(|| format!("Invalid separator {} in placeholder", quoted))?;
+
+    let mut separator = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => separator.extend(chars.next()),
+            _ => separator.push(c),
+        }
+    }
+    Ok(separator.into())
+}
+
+/// Parse the spec after `:`: `:`-separated items, each a width (`8`), a character
+/// set (`[a-z]`, `[^,]`), a radix (`x`, `#4x`, `i`) or a type (`i32`).
+fn parse_spec(spec: &str) -> Result<FormatSpec, String> {
+    let mut parsed = FormatSpec::default();
+
+    for item in split_spec_items(spec) {
+        if item.is_empty() {
+            continue;
+        }
+
+        if item.starts_with('[') {
+            if parsed.class.is_some() {
+                return Err(format!("Character set given twice in spec '{}'", spec));
+            }
+            parsed.class = Some(parse_char_class(item)?);
+            continue;
+        }
+
+        if let Some(alternatives) = parse_alternatives(item) {
+            if parsed.alternatives.is_some() {
+                return Err(format!("Alternatives given twice in spec '{}'", spec));
+            }
+            parsed.alternatives = Some(alternatives?);
+            continue;
+        }
+
+        if item.starts_with('/') {
+            if parsed.regex.is_some() {
+                return Err(format!("Regex given twice in spec '{}'", spec));
+            }
+            parsed.regex = Some(parse_regex(item)?);
+            continue;
+        }
+
+        if item == "q" {
+            if parsed.quoted {
+                return Err(format!(
+                    "Quoted specifier 'q' given twice in spec '{}'",
+                    spec
+                ));
+            }
+            parsed.quoted = true;
+            continue;
+        }
+
+        if item == "last" {
+            if parsed.last {
+                return Err(format!("Modifier 'last' given twice in spec '{}'", spec));
+            }
+            parsed.last = true;
+            continue;
+        }
+
+        if let Some((width, radix)) = parse_radix(item)? {
+            if parsed.radix.is_some() {
+                return Err(format!("Placeholder radix given twice in spec '{}'", spec));
+            }
+            parsed.radix = Some(radix);
+            if !width.is_empty() {
+                parse_width(&mut parsed, width, spec)?;
+            }
+            continue;
+        }
+
+        if item.chars().all(|c| c.is_ascii_digit()) {
+            parse_width(&mut parsed, item, spec)?;
+            continue;
+        }
+
+        if parsed.ty.is_some() {
+            return Err(format!("Placeholder type given twice in spec '{}'", spec));
+        }
+        if syn::parse_str::<syn::Type>(item).is_err() {
+            return Err(format!(
+                "Invalid format spec '{}' in placeholder. \
+                 Expected a width in characters, a radix, 'q', 'last' or a type, \
+                 e.g. '{{:8}}', '{{:x}}' or '{{name:i32}}'",
+                item
+            ));
+        }
+        parsed.ty = Some(item.into());
+    }
+
+    let delimiters = [
+        parsed.width.is_some(),
+        parsed.class.is_some(),
+        parsed.regex.is_some(),
+        parsed.alternatives.is_some(),
+        parsed.quoted,
+    ];
+    let delimiter_count = delimiters.into_iter().filter(|&given| given).count();
+    if delimiter_count > 1 {
+        return Err(format!(
+            "Placeholder spec '{}' combines a width, a character set, a regex, alternatives \
+             or 'q'; use one of them",
+            spec
+        ));
+    }
+    if parsed.last && delimiter_count > 0 {
+        return Err(format!(
+            "Modifier 'last' in spec '{}' applies to fields ended by the text after them; \
+             it cannot be combined with a width, a character set, a regex, alternatives or 'q'",
+            spec
+        ));
+    }
+
+    Ok(parsed)
+}
+
+/// Set the width from an all-digits item.
+fn parse_width(parsed: &mut FormatSpec, item: &str, spec: &str) -> Result<(), String> {
+    if parsed.width.is_some() {
+        return Err(format!("Placeholder width given twice in spec '{}'", spec));
+    }
+    match item.parse::<usize>() {
+        Ok(0) => Err("Placeholder width must be greater than zero".to_string()),
+        Ok(width) => {
+            parsed.width = Some(width);
+            Ok(())
+        }
+        Err(_) => Err(format!("Placeholder width '{}' is too large", item)),
+    }
+}
+
+/// Split a radix item written like Rust's `{:#4x}` (`#`, width digits, then `x`, `X`, `o`,
+/// `b` or `i`) into its width digits and radix. `None` if the item is not a radix.
+fn parse_radix(item: &str) -> Result<Option<(&str, Radix)>, String> {
+    let Some(letter) = item.chars().last() else {
+        return Ok(None);
+    };
+    let rest = &item[..item.len() - letter.len_utf8()];
+    let (prefixed, width) = match rest.strip_prefix('#') {
+        Some(width) => (true, width),
+        None => (false, rest),
+    };
+    if !width.chars().all(|c| c.is_ascii_digit()) {
+        return Ok(None);
+    }
+
+    let base = match letter {
+        'x' | 'X' => 16,
+        'o' => 8,
+        'b' => 2,
+        'i' if prefixed => {
+            return Err(
+                "'#' is not allowed with radix 'i', which already detects prefixes".to_string(),
+            );
+        }
+        'i' => return Ok(Some((width, Radix::Auto))),
+        _ => return Ok(None),
+    };
+    Ok(Some((width, Radix::Base { base, prefixed })))
+}
+
+/// Split an `(A|B|C)` item into its alternatives, unescaping `\|`, `\)` and `\\`.
+/// `None` if the item is not a group with at least one unescaped `|`, like `(u8, u8)`.
+fn parse_alternatives(item: &str) -> Option<Result<Vec<Box<str>>, String>> {
+    let body = item.strip_prefix('(')?.strip_suffix(')')?;
+
+    let mut alternatives = Vec::new();
+    let mut current = String::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            '|' => alternatives.push(std::mem::take(&mut current).into_boxed_str()),
+            _ => current.push(c),
+        }
+    }
+    if alternatives.is_empty() {
+        return None;
+    }
+    alternatives.push(current.into_boxed_str());
+
+    if alternatives
+        .iter()
+        .any(|alternative| alternative.is_empty())
+    {
+        return Some(Err(format!("Empty alternative in '{}'", item)));
+    }
+    Some(Ok(alternatives))
+}
+
+/// Strip the `/` delimiters of a regex item and unescape `\/`; other escapes are the regex's.
+fn parse_regex(item: &str) -> Result<Box<str>, String> {
+    let body = item
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+        .filter(|body| !body.is_empty())
+        .ok_or_else(|| format!("Invalid regex '{}' in placeholder", item))?;
+
+    let mut source = String::with_capacity(body.len());
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('/')) => {}
+            ('\\', Some(escaped)) => {
+                source.push(c);
+                source.push(escaped);
+                chars.next();
+            }
+            _ => source.push(c),
+        }
+    }
+    Ok(source.into())
+}
+
+/// Parse a C-style set: `[a-z0-9_]`, negated `[^,]`. `\` escapes, `-` first/last is literal.
+fn parse_char_class(item: &str) -> Result<CharClass, String> {
+    let body = item
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| format!("Invalid character set '{}' in placeholder", item))?;
+    let (negated, body) = match body.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, body),
+    };
+
+    let mut members = Vec::new();
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        let c = if c == '\\' {
+            chars
+                .next()
+                .ok_or_else(|| format!("Dangling '\\' in character set '{}'", item))?
+        } else {
+            c
+        };
+        members.push(c);
+    }
+
+    if members.is_empty() {
+        return Err(format!("Empty character set '{}' in placeholder", item));
+    }
+
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < members.len() {
+        let start = members[index];
+        let is_range = index + 2 < members.len() && members[index + 1] == '-';
+        if is_range {
+            let end = members[index + 2];
+            if start > end {
+                return Err(format!(
+                    "Invalid range '{}-{}' in character set '{}'",
+                    start, end, item
+                ));
+            }
+            ranges.push((start, end));
+            index += 3;
+        } else {
+            ranges.push((start, start));
+            index += 1;
+        }
+    }
+
+    Ok(CharClass {
+        source: item.into(),
+        negated,
+        ranges,
+    })
+}
+
+/// Split a spec on single `:`, keeping paths (`std::net::Ipv4Addr`), generics and
+/// character sets intact.
+fn split_spec_items(spec: &str) -> Vec<&str> {
+    let bytes = spec.as_bytes();
+    let mut items = Vec::new();
+    let mut depth: usize = 0;
+    // Bytes read since the opening `[`, `None` outside a set (see `read_placeholder_content`)
+    let mut set_len: Option<usize> = None;
+    // Closing byte of the regex or group being read
+    let mut closing: Option<u8> = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, &byte) in bytes.iter().enumerate() {
+        if let Some(end) = closing {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                _ if byte == end => closing = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Some(len) = set_len {
+            set_len = match byte {
+                _ if escaped => {
+                    escaped = false;
+                    Some(len + 1)
+                }
+                b'\\' => {
+                    escaped = true;
+                    Some(len + 1)
+                }
+                b']' if len > 0 && !(len == 1 && bytes[index - 1] == b'^') => None,
+                _ => Some(len + 1),
+            };
+            continue;
+        }
+
+        match byte {
+            b'[' => set_len = Some(0),
+            b'/' if index == start => closing = Some(b'/'),
+            b'(' if index == start => closing = Some(b')'),
+            b'<' => depth += 1,
+            b'>' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 => {
+                let in_path =
+                    bytes.get(index + 1) == Some(&b':') || (index > 0 && bytes[index - 1] == b':');
+                if !in_path {
+                    items.push(&spec[start..index]);
+                    start = index + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    items.push(&spec[start..]);
+
+    items
+}
+
+/// Compile error pointing at the format string literal.
+fn make_format_error(format_lit: &LitStr, message: String) -> TokenStream {
+    syn::Error::new(format_lit.span(), message)
+        .to_compile_error()
+        .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,4 +744,275 @@ mod tests {
         let tokens = result.unwrap();
         assert_eq!(tokens.len(), 1); // Single text token with literal braces
     }
+
+    #[test]
+    fn test_width_spec() {
+        let format_lit: LitStr = syn::parse_quote!("{:8}{date:2}");
+        let tokens = tokenize_format_string("{:8}{date:2}", &format_lit).unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert!(matches!(
+            &tokens[0],
+            FormatToken::Placeholder(Placeholder::Anonymous(spec)) if spec.width == Some(8)
+        ));
+        assert!(matches!(
+            &tokens[1],
+            FormatToken::Placeholder(Placeholder::Named(name, spec))
+                if &**name == "date" && spec.width == Some(2)
+        ));
+    }
+
+    #[test]
+    fn test_type_spec() {
+        let Ok(Placeholder::Named(name, spec)) = parse_placeholder("addr:std::net::Ipv4Addr:15")
+        else {
+            panic!("expected named placeholder");
+        };
+        assert_eq!(&*name, "addr");
+        assert_eq!(spec.ty.as_deref(), Some("std::net::Ipv4Addr"));
+        assert_eq!(spec.width, Some(15));
+
+        let Ok(Placeholder::Anonymous(spec)) = parse_placeholder(":Vec<u8>") else {
+            panic!("expected anonymous placeholder");
+        };
+        assert_eq!(spec.ty.as_deref(), Some("Vec<u8>"));
+    }
+
+    #[test]
+    fn test_invalid_specs_rejected() {
+        assert!(parse_placeholder(":0").is_err());
+        assert!(parse_placeholder(":8z").is_err());
+        assert!(parse_placeholder(":i32:u8").is_err());
+        assert!(parse_placeholder(":4:4").is_err());
+        assert!(parse_placeholder("x:99999999999999999999999").is_err());
+        assert_eq!(
+            parse_placeholder("x:"),
+            Ok(Placeholder::Named("x".into(), FormatSpec::default()))
+        );
+    }
+
+    #[test]
+    fn test_discard_placeholder() {
+        assert_eq!(
+            parse_placeholder("_"),
+            Ok(Placeholder::Discard(FormatSpec::default()))
+        );
+        assert_eq!(
+            parse_placeholder("*"),
+            Ok(Placeholder::Discard(FormatSpec::default()))
+        );
+        let Ok(Placeholder::Discard(spec)) = parse_placeholder("_:i32") else {
+            panic!("expected discard placeholder");
+        };
+        assert_eq!(spec.ty.as_deref(), Some("i32"));
+    }
+
+    #[test]
+    fn test_indexed_placeholder() {
+        assert_eq!(
+            parse_placeholder("1"),
+            Ok(Placeholder::Indexed(1, FormatSpec::default()))
+        );
+        let Ok(Placeholder::Indexed(0, spec)) = parse_placeholder("0:x") else {
+            panic!("expected indexed placeholder");
+        };
+        assert!(spec.radix.is_some());
+        assert!(parse_placeholder("99999999999999999999999").is_err());
+        assert!(parse_placeholder("1a").is_err());
+    }
+
+    #[test]
+    fn test_field_path_placeholder() {
+        let Ok(Placeholder::Named(name, spec)) = parse_placeholder("grid[1].cells[0]:u8:2") else {
+            panic!("expected named placeholder");
+        };
+        assert_eq!(&*name, "grid[1].cells[0]");
+        assert_eq!(spec.width, Some(2));
+        assert!(parse_placeholder("point.").is_err());
+        assert!(parse_placeholder("arr[i]").is_err());
+    }
+
+    #[test]
+    fn test_repeat_separator() {
+        let repeat = |content| parse_placeholder(content).map(|ph| ph.spec().repeat.clone());
+        assert_eq!(repeat("nums*,"), Ok(Some(",".into())));
+        assert_eq!(repeat("**"), Ok(Some("*".into())));
+        assert_eq!(
+            repeat(r#"nums:Vec<u8>:x;sep=":\"""#),
+            Ok(Some(":\"".into()))
+        );
+        assert!(matches!(
+            parse_placeholder("nums*,"),
+            Ok(Placeholder::Named(name, _)) if &*name == "nums"
+        ));
+        assert!(parse_placeholder(r#"nums*,;sep=",""#).is_err());
+        assert!(parse_placeholder(r#"nums;sep="""#).is_err());
+        assert!(parse_placeholder("nums*").is_err());
+        assert_eq!(
+            read_placeholder_content(&mut r#"n;sep="}"}"#.chars().peekable()),
+            Ok(r#"n;sep="}""#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_radix_spec() {
+        let radix = |content| parse_placeholder(content).map(|ph| ph.spec().clone());
+        assert_eq!(
+            radix(":x").unwrap().radix,
+            Some(Radix::Base {
+                base: 16,
+                prefixed: false
+            })
+        );
+        let spec = radix("n:u16:#4o").unwrap();
+        assert_eq!(
+            (spec.ty.as_deref(), spec.width, spec.radix),
+            (
+                Some("u16"),
+                Some(4),
+                Some(Radix::Base {
+                    base: 8,
+                    prefixed: true
+                })
+            )
+        );
+        assert_eq!(radix(":i").unwrap().radix, Some(Radix::Auto));
+        assert!(radix(":#i").is_err());
+        assert!(radix(":x:b").is_err());
+        assert!(radix(":4:2x").is_err());
+    }
+
+    #[test]
+    fn test_regex_spec() {
+        let format_lit: LitStr = syn::parse_quote!("{}");
+        let tokens = tokenize_format_string(r"{v:u8:/\d{1,3}[:}]\//}.", &format_lit).unwrap();
+        let FormatToken::Placeholder(Placeholder::Named(name, spec)) = &tokens[0] else {
+            panic!("expected named placeholder");
+        };
+        assert_eq!(&**name, "v");
+        assert_eq!(spec.ty.as_deref(), Some("u8"));
+        assert_eq!(spec.regex.as_deref(), Some(r"\d{1,3}[:}]/"));
+        assert!(matches!(&tokens[1], FormatToken::Text(text) if &**text == "."));
+
+        assert!(parse_placeholder("://").is_err());
+        assert!(parse_placeholder(":/a/:/b/").is_err());
+        assert!(parse_placeholder(":4:/a/").is_err());
+        assert!(read_placeholder_content(&mut r":/a\/}".chars().peekable()).is_err());
+    }
+
+    #[test]
+    fn test_alternatives_spec() {
+        let format_lit: LitStr = syn::parse_quote!("{}");
+        let tokens = tokenize_format_string(r"{m:&str:(GET|a\|b|\):})} ", &format_lit).unwrap();
+        let FormatToken::Alternation(Placeholder::Named(name, spec)) = &tokens[0] else {
+            panic!("expected alternation");
+        };
+        assert_eq!(&**name, "m");
+        assert_eq!(spec.ty.as_deref(), Some("&str"));
+        let alternatives: Vec<&str> = spec.alternatives.iter().flatten().map(|a| &**a).collect();
+        assert_eq!(alternatives, ["GET", "a|b", "):}"]);
+
+        let spec = parse_placeholder(":(u8, u8)").unwrap().spec().clone();
+        assert_eq!(
+            (spec.ty.as_deref(), spec.alternatives),
+            (Some("(u8, u8)"), None)
+        );
+
+        assert!(parse_placeholder(":(a||b)").is_err());
+        assert!(parse_placeholder(":(a|b):4").is_err());
+        assert!(parse_placeholder("v*,:(a|b)").is_err());
+        assert!(read_placeholder_content(&mut ":(a|b}".chars().peekable()).is_err());
+    }
+
+    #[test]
+    fn test_last_spec() {
+        let spec = parse_placeholder("path:last:String")
+            .unwrap()
+            .spec()
+            .clone();
+        assert!(spec.last);
+        assert_eq!(spec.ty.as_deref(), Some("String"));
+        assert!(parse_placeholder("nums*,:last").unwrap().spec().last);
+        assert!(parse_placeholder(":last:last").is_err());
+        assert!(parse_placeholder(":last:4").is_err());
+        assert!(parse_placeholder(":last:q").is_err());
+    }
+
+    #[test]
+    fn test_quoted_spec() {
+        let spec = parse_placeholder("name:String:q").unwrap().spec().clone();
+        assert!(spec.quoted);
+        assert_eq!(spec.ty.as_deref(), Some("String"));
+        assert!(parse_placeholder(":q:q").is_err());
+        assert!(parse_placeholder(":q:8").is_err());
+        assert!(parse_placeholder(":q:[a-z]").is_err());
+    }
+
+    #[test]
+    fn test_optional_sections() {
+        let format_lit: LitStr = syn::parse_quote!("{}");
+        let tokens = tokenize_format_string("{a}[[ ({b}[[,{c}]]?)]]?]]", &format_lit).unwrap();
+        assert_eq!(tokens.len(), 3);
+        let FormatToken::Optional(section) = &tokens[1] else {
+            panic!("expected optional section");
+        };
+        assert!(matches!(&section[0], FormatToken::Text(text) if &**text == " ("));
+        assert!(matches!(&section[2], FormatToken::Optional(inner) if inner.len() == 2));
+        assert!(matches!(&section[3], FormatToken::Text(text) if &**text == ")"));
+        assert!(matches!(&tokens[2], FormatToken::Text(text) if &**text == "]]"));
+    }
+
+    #[test]
+    fn test_unpaired_section_markers_are_text() {
+        let format_lit: LitStr = syn::parse_quote!("{}");
+        let tokens = tokenize_format_string("[[{a}]]", &format_lit).unwrap();
+        assert!(matches!(&tokens[0], FormatToken::Text(text) if &**text == "[["));
+        assert!(matches!(&tokens[2], FormatToken::Text(text) if &**text == "]]"));
+
+        let tokens = tokenize_format_string("{a}]]?", &format_lit).unwrap();
+        assert!(matches!(&tokens[1], FormatToken::Text(text) if &**text == "]]?"));
+
+        // The outer `[[` is unclosed; the inner one still opens a section
+        let tokens = tokenize_format_string("[[{a} [[{b}]]?", &format_lit).unwrap();
+        assert!(matches!(&tokens[0], FormatToken::Text(text) if &**text == "[["));
+        assert!(matches!(&tokens[3], FormatToken::Optional(inner) if inner.len() == 1));
+
+        let tokens = tokenize_format_string("{:[[]}[[x]]?", &format_lit).unwrap();
+        assert!(matches!(&tokens[1], FormatToken::Optional(_)));
+    }
+
+    #[test]
+    fn test_char_class_spec() {
+        let Ok(Placeholder::Named(_, spec)) = parse_placeholder("n:u32:[0-9_]") else {
+            panic!("expected named placeholder");
+        };
+        let class = spec.class.unwrap();
+        assert_eq!(spec.ty.as_deref(), Some("u32"));
+        assert!(!class.negated);
+        assert_eq!(class.ranges, vec![('0', '9'), ('_', '_')]);
+
+        let format_lit: LitStr = syn::parse_quote!("{}");
+        let tokens = tokenize_format_string("{:[]}a]}", &format_lit).unwrap();
+        let FormatToken::Placeholder(Placeholder::Anonymous(spec)) = &tokens[0] else {
+            panic!("expected anonymous placeholder");
+        };
+        assert_eq!(
+            spec.class.as_ref().unwrap().ranges,
+            vec![(']', ']'), ('}', '}'), ('a', 'a')]
+        );
+
+        let tokens = tokenize_format_string("{:[^:,]},", &format_lit).unwrap();
+        let FormatToken::Placeholder(Placeholder::Anonymous(spec)) = &tokens[0] else {
+            panic!("expected anonymous placeholder");
+        };
+        assert!(spec.class.as_ref().unwrap().negated);
+        assert_eq!(
+            spec.class.as_ref().unwrap().ranges,
+            vec![(':', ':'), (',', ',')]
+        );
+
+        assert!(parse_placeholder(":[z-a]").is_err());
+        assert!(parse_placeholder(":[^]").is_err());
+        assert!(parse_placeholder(":4:[a]").is_err());
+        assert!(read_placeholder_content(&mut ":[a-z}".chars().peekable()).is_err());
+    }
 }
@@ -1,19 +1,84 @@
 //! Core types for scanf macros.
 
-/// Placeholder in format string: `{name}` or `{}`.
+/// Placeholder in format string: `{name}`, `{}`, `{0}` or `{_}`, each with an optional `:spec`.
 ///
 /// Named uses `Box<str>` (16 bytes) vs `String` (24 bytes) for 33% memory saving.
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Placeholder {
-    Named(Box<str>),
-    Anonymous,
+    Named(Box<str>, FormatSpec),
+    Anonymous(FormatSpec),
+    /// `{0}`, `{1}`: the explicit argument at that 0-based position, as in `format!`.
+    Indexed(usize, FormatSpec),
+    /// `{_}` or `{*}`: matched but not stored, like C's `%*d`. Parsed only when typed.
+    Discard(FormatSpec),
 }
 
-/// Format string token: literal text or placeholder.
+impl Placeholder {
+    /// Spec written after `:` (default when absent).
+    pub fn spec(&self) -> &FormatSpec {
+        match self {
+            Placeholder::Named(_, spec)
+            | Placeholder::Anonymous(spec)
+            | Placeholder::Indexed(_, spec)
+            | Placeholder::Discard(spec) => spec,
+        }
+    }
+}
+
+/// Placeholder spec: `{:8}`, `{date:8}`, `{x:i32}`, `{x:u32:8}`, `{:[a-z]}`, `{:#x}`, `{:/\d+/}`,
+/// `{method:(GET|POST)}`, `{:q}`, `{path:last}`.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FormatSpec {
+    /// Inline type annotation, validated as a Rust type during tokenization.
+    pub ty: Option<Box<str>>,
+    /// Fixed width in characters. Field ends after exactly this many chars.
+    pub width: Option<usize>,
+    /// Character set. Field is the longest non-empty run of matching chars.
+    pub class: Option<CharClass>,
+    /// Integer radix, parsed with `from_str_radix` instead of `FromStr`.
+    pub radix: Option<Radix>,
+    /// Separator of a repeated capture into `Vec<T>`: `{nums*,}`, `{nums:Vec<u32>;sep=","}`.
+    pub repeat: Option<Box<str>>,
+    /// `(A|B)` alternatives: the field is the first one found at the current position.
+    pub alternatives: Option<Vec<Box<str>>>,
+    /// `q`: a `"..."` or `'...'` string; the field is its contents with escapes resolved.
+    pub quoted: bool,
+    /// `last`: the field ends at the last occurrence of the text after it, searched up to
+    /// the next literal text of the format.
+    pub last: bool,
+    /// Regex between `/.../` (`\/` unescaped). Field is its match anchored at the current position.
+    pub regex: Option<Box<str>>,
+}
+
+/// Integer radix: `x`/`X` (16), `o` (8), `b` (2) or `i` (C's `%i`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Radix {
+    /// Fixed base. The `0x`/`0o`/`0b` prefix is optional, or required when `prefixed` (`#x`).
+    Base { base: u32, prefixed: bool },
+    /// Base from the prefix: `0x` hex, `0o` or leading `0` octal, `0b` binary, else decimal.
+    Auto,
+}
+
+/// C-style `%[...]` set: `[a-z0-9]`, or `[^,]` when negated.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CharClass {
+    /// Set as written, for error messages.
+    pub source: Box<str>,
+    pub negated: bool,
+    /// Inclusive ranges; single chars are `(c, c)`.
+    pub ranges: Vec<(char, char)>,
+}
+
+/// Format string token: literal text, placeholder or optional section.
 ///
 /// Text uses `Box<str>` (16 bytes) vs `String` (24 bytes) for 33% memory saving.
 #[derive(Debug, Clone)]
 pub enum FormatToken {
     Text(Box<str>),
     Placeholder(Placeholder),
+    /// `[[...]]?`: matched when possible, otherwise input and captures are left as before.
+    Optional(Vec<FormatToken>),
+    /// Placeholder with `(A|B)` alternatives: matched like literal text, so it also ends
+    /// the previous field, then captured like a placeholder.
+    Alternation(Placeholder),
 }
@@ -26,6 +26,43 @@ pub fn is_valid_identifier(s: &str) -> bool {
     chars.all(|c| c.is_alphanumeric() || c == '_')
 }
 
+/// Check if string is a place to assign to: `name`, `point.x`, `self.name`, `pair.0` or
+/// `arr[0]`, with every named segment a valid identifier.
+pub fn is_valid_field_path(s: &str) -> bool {
+    let Some(end) = s.find(['.', '[']) else {
+        return is_valid_identifier(s);
+    };
+    let (root, mut rest) = s.split_at(end);
+    if root != "self" && !is_valid_identifier(root) {
+        return false;
+    }
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let segment = &after[..end];
+            if !is_valid_identifier(segment) && !is_index(segment) {
+                return false;
+            }
+            rest = &after[end..];
+        } else if let Some((index, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']'))
+        {
+            if !is_index(index) {
+                return false;
+            }
+            rest = after;
+        } else {
+            return false;
+        }
+    }
+    true
+}
+
+/// Tuple field or array index: ASCII digits.
+fn is_index(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|byte| byte.is_ascii_digit())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +96,23 @@ mod tests {
         assert!(!is_valid_identifier("async"));
         assert!(!is_valid_identifier("await"));
     }
+
+    #[test]
+    fn test_field_paths() {
+        assert!(is_valid_field_path("x"));
+        assert!(is_valid_field_path("point.x"));
+        assert!(is_valid_field_path("self.name"));
+        assert!(is_valid_field_path("pair.0"));
+        assert!(is_valid_field_path("grid[2][10].cell"));
+
+        assert!(!is_valid_field_path("self"));
+        assert!(!is_valid_field_path("point."));
+        assert!(!is_valid_field_path("point.type"));
+        assert!(!is_valid_field_path("a.self"));
+        assert!(!is_valid_field_path("arr[i]"));
+        assert!(!is_valid_field_path("arr[0"));
+        assert!(!is_valid_field_path("arr[]"));
+        assert!(!is_valid_field_path("[0]"));
+        assert!(!is_valid_field_path("a..b"));
+    }
 }
@@ -0,0 +1,58 @@
+#![cfg(feature = "tokio")]
+
+use scanf::{ascan_lines, ascanf};
+use tokio::io::{AsyncWriteExt, BufReader};
+
+#[tokio::test]
+async fn test_ascanf_over_duplex_stream() {
+    let (mut client, server) = tokio::io::duplex(64);
+    let mut reader = BufReader::new(server);
+
+    client.write_all(b"alice 30\r\nbob 25\n").await.unwrap();
+    drop(client);
+
+    let mut name: String = String::new();
+    let mut age: u8 = 0;
+    ascanf!(&mut reader, "{name} {age}").await.unwrap();
+    assert_eq!((name.as_str(), age), ("alice", 30));
+    ascanf!(&mut reader, "{} {}", &mut name, &mut age)
+        .await
+        .unwrap();
+    assert_eq!((name.as_str(), age), ("bob", 25));
+
+    let error = ascanf!(&mut reader, "{name} {age}").await.unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn test_ascan_lines_over_duplex_stream() {
+    let (mut client, server) = tokio::io::duplex(64);
+
+    let writer = tokio::spawn(async move {
+        for line in ["GET /", "PUT /upload", "bogus"] {
+            client.write_all(line.as_bytes()).await.unwrap();
+            client.write_all(b"\n").await.unwrap();
+        }
+    });
+
+    let mut requests = ascan_lines!(BufReader::new(server), "{method} {path}");
+    let mut parsed: Vec<(String, String)> = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(request) = requests.next_line().await {
+        match request {
+            Ok(request) => parsed.push(request),
+            Err(error) => errors.push(error.to_string()),
+        }
+    }
+    writer.await.unwrap();
+
+    assert_eq!(
+        parsed,
+        vec![
+            ("GET".to_string(), "/".to_string()),
+            ("PUT".to_string(), "/upload".to_string()),
+        ]
+    );
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Line 3: "));
+}
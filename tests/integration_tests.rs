@@ -1,4 +1,4 @@
-use scanf::sscanf;
+use scanf::{Scanf, bscanf, fscanf, scan_lines, scanf_match, sscan, sscanf, sscanf_let, sscanf_ws};
 
 #[test]
 fn test_legacy_basic_functionality() {
@@ -317,6 +317,1109 @@ fn test_empty_string_field_parsing() {
     assert_eq!(marker, "end");
 }
 
+#[test]
+fn test_fixed_width_fields() {
+    // Fixed-column records: adjacent placeholders split by declared widths
+    let input = "20261016AB0042";
+    let mut date: u32 = 0;
+    let mut code: String = String::new();
+    let mut amount: u16 = 0;
+    sscanf!(input, "{date:8}{code:2}{amount:4}").unwrap();
+    assert_eq!(date, 20261016);
+    assert_eq!(code, "AB");
+    assert_eq!(amount, 42);
+}
+
+#[test]
+fn test_fixed_width_anonymous_with_unbounded_last() {
+    let input = "2026-10-16 rest of line";
+    let mut year: u16 = 0;
+    let mut tail: String = String::new();
+    sscanf!(input, "{:4}-10-16 {}", &mut year, &mut tail).unwrap();
+    assert_eq!(year, 2026);
+    assert_eq!(tail, "rest of line");
+}
+
+#[test]
+fn test_fixed_width_counts_chars() {
+    let input = "ñandú42";
+    let mut word: String = String::new();
+    let mut number: u8 = 0;
+    sscanf!(input, "{word:5}{number}").unwrap();
+    assert_eq!(word, "ñandú");
+    assert_eq!(number, 42);
+}
+
+#[test]
+fn test_fixed_width_input_too_short() {
+    let input = "2026";
+    let mut date: u32 = 0;
+    let result = sscanf!(input, "{date:8}");
+    assert!(result.is_err());
+    assert_eq!(date, 0);
+}
+
+#[test]
+fn test_inline_type_annotation() {
+    let input = "7 items";
+    let mut count: u64 = 0;
+    sscanf!(input, "{count:u64} items").unwrap();
+    assert_eq!(count, 7);
+}
+
+#[test]
+fn test_sscanf_let_typed_bindings() {
+    let input = "42,hello";
+    sscanf_let!(input, "{x:i32},{name:String}" else {
+        panic!("input should parse");
+    });
+    assert_eq!(x, 42);
+    assert_eq!(name, "hello");
+}
+
+#[test]
+fn test_sscanf_let_inferred_binding() {
+    fn double(value: u8) -> u8 {
+        value * 2
+    }
+
+    let input = "id=21";
+    sscanf_let!(input, "id={id}" else { return });
+    assert_eq!(double(id), 42);
+}
+
+#[test]
+fn test_sscanf_let_runs_else_on_failure() {
+    let mut failures = 0;
+    for input in ["1:2", "x:2", "3"] {
+        sscanf_let!(input, "{a:u8}:{b:u8}" else {
+            failures += 1;
+            continue;
+        });
+        assert_eq!((a, b), (1, 2));
+    }
+    assert_eq!(failures, 2);
+}
+
+#[test]
+fn test_sscanf_let_fixed_width_bindings() {
+    let input = "20261016AB";
+    sscanf_let!(input, "{year:u16:4}{month:u8:2}{day:u8:2}{code:String}" else {
+        panic!("input should parse");
+    });
+    assert_eq!((year, month, day), (2026, 10, 16));
+    assert_eq!(code, "AB");
+}
+
+#[test]
+fn test_sscan_returns_tuple() -> std::io::Result<()> {
+    let (w, h): (u32, u32) = sscan!("1920x1080", "{}x{}")?;
+    assert_eq!((w, h), (1920, 1080));
+    Ok(())
+}
+
+#[test]
+fn test_sscan_single_value_and_inline_type() {
+    let port = sscan!("port=8080", "port={:u16}").unwrap();
+    assert_eq!(port, 8080);
+}
+
+#[test]
+fn test_sscan_named_placeholders_in_order() {
+    let (name, age): (String, u8) = sscan!("Alice: 30", "{name}: {age}").unwrap();
+    assert_eq!(name, "Alice");
+    assert_eq!(age, 30);
+}
+
+#[test]
+fn test_sscan_type_without_default() {
+    let addr: std::net::Ipv4Addr = sscan!("host 10.0.0.1", "host {}").unwrap();
+    assert_eq!(addr, std::net::Ipv4Addr::new(10, 0, 0, 1));
+}
+
+#[test]
+fn test_sscan_error() {
+    let parsed: std::io::Result<(u8, u8)> = sscan!("1x300", "{}x{}");
+    let error = parsed.unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("anonymous placeholder #2"));
+}
+
+#[derive(Scanf, Debug, PartialEq)]
+#[scanf("({x},{y})")]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Scanf, Debug, PartialEq)]
+#[scanf("{}x{}")]
+struct Size(u32, u32);
+
+#[derive(Scanf, Debug, PartialEq)]
+#[scanf("{name:String} <{email}> age {age:8}")]
+struct Contact {
+    age: u8,
+    name: String,
+    email: String,
+}
+
+#[test]
+fn test_derive_named_fields() {
+    let point: Point = "(3,-4)".parse().unwrap();
+    assert_eq!(point, Point { x: 3, y: -4 });
+}
+
+#[test]
+fn test_derive_tuple_struct() {
+    let size: Size = "640x480".parse().unwrap();
+    assert_eq!(size, Size(640, 480));
+}
+
+#[test]
+fn test_derive_fields_in_any_order() {
+    let contact: Contact = "Ann <ann@example.com> age 00000042".parse().unwrap();
+    assert_eq!(
+        contact,
+        Contact {
+            age: 42,
+            name: "Ann".to_string(),
+            email: "ann@example.com".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_derive_rejects_invalid_and_trailing_input() {
+    assert!("(3,x)".parse::<Point>().is_err());
+    assert!("(3,4) trailing".parse::<Point>().is_err());
+    assert!("3,4".parse::<Point>().is_err());
+}
+
+#[derive(Scanf, Debug, PartialEq)]
+enum Command {
+    #[scanf("MOVE {x} {y}")]
+    Move { x: i32, y: i32 },
+    #[scanf("SAY {}")]
+    Say(String),
+    #[scanf("QUIT")]
+    Quit,
+    #[scanf("{}")]
+    Raw(String),
+}
+
+#[test]
+fn test_derive_enum_variants() {
+    assert_eq!(
+        "MOVE 1 -2".parse::<Command>().unwrap(),
+        Command::Move { x: 1, y: -2 }
+    );
+    assert_eq!(
+        "SAY hi there".parse::<Command>().unwrap(),
+        Command::Say("hi there".to_string())
+    );
+    assert_eq!("QUIT".parse::<Command>().unwrap(), Command::Quit);
+}
+
+#[test]
+fn test_derive_enum_first_full_match_wins() {
+    // "MOVE" fails on the coordinates and "QUIT" needs the whole input
+    assert_eq!(
+        "MOVE north".parse::<Command>().unwrap(),
+        Command::Raw("MOVE north".to_string())
+    );
+    assert_eq!(
+        "QUIT now".parse::<Command>().unwrap(),
+        Command::Raw("QUIT now".to_string())
+    );
+}
+
+#[derive(Scanf, Debug, PartialEq)]
+enum Strict {
+    #[scanf("ON")]
+    On,
+    #[scanf("LEVEL {}")]
+    Level(u8),
+}
+
+#[test]
+fn test_derive_enum_no_match_reports_each_variant() {
+    assert_eq!("LEVEL 3".parse::<Strict>().unwrap(), Strict::Level(3));
+    let error = "LEVEL high".parse::<Strict>().unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains("matches no variant of Strict"));
+    assert!(message.contains("On: "));
+    assert!(message.contains("Level: "));
+}
+
+fn route(line: &str) -> String {
+    scanf_match!(line, {
+        "GET {path:String}" => format!("get {}", path),
+        "PUT {path:String} {len:usize}" if len > 0 => format!("put {} {}", path, len),
+        "PUT {path:String} {len:usize}" => format!("truncate {} to {}", path, len),
+        _ => "unknown".to_string(),
+    })
+}
+
+#[test]
+fn test_scanf_match_arms_and_guards() {
+    assert_eq!(route("GET /index.html"), "get /index.html");
+    assert_eq!(route("PUT /upload 42"), "put /upload 42");
+    assert_eq!(route("PUT /upload 0"), "truncate /upload to 0");
+    assert_eq!(route("PUT /upload many"), "unknown");
+    assert_eq!(route("DELETE /"), "unknown");
+}
+
+#[test]
+fn test_scanf_match_does_not_clobber_outer_variables() {
+    let path = "outer";
+    let line = String::from("PUT /file x");
+    let matched = scanf_match!(line, {
+        "PUT {path} {len:u32}" => {
+            let _: &String = &path;
+            len
+        }
+        _ => 0,
+    });
+    assert_eq!(matched, 0);
+    assert_eq!(path, "outer");
+}
+
+#[test]
+fn test_scanf_match_requires_full_match() {
+    let kind = scanf_match!("QUIT now", {
+        "QUIT" => 1,
+        "QUIT {reason:String}" => reason.len(),
+        _ => 0,
+    });
+    assert_eq!(kind, 3);
+}
+
+#[test]
+fn test_fscanf_reads_line_by_line() {
+    let mut reader = std::io::Cursor::new("alice 30\r\nbob 25\n");
+    let mut name: String = String::new();
+    let mut age: u8 = 0;
+    fscanf!(&mut reader, "{name} {age}").unwrap();
+    assert_eq!((name.as_str(), age), ("alice", 30));
+    fscanf!(&mut reader, "{} {}", &mut name, &mut age).unwrap();
+    assert_eq!((name.as_str(), age), ("bob", 25));
+}
+
+#[test]
+fn test_fscanf_reports_eof() {
+    let mut reader = std::io::Cursor::new("");
+    let mut value: i32 = 0;
+    let error = fscanf!(&mut reader, "{value}").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    assert_eq!(value, 0);
+}
+
+#[test]
+fn test_fscanf_generic_reader() {
+    fn read_pair(reader: &mut impl std::io::BufRead) -> std::io::Result<(i32, i32)> {
+        let mut x: i32 = 0;
+        let mut y: i32 = 0;
+        fscanf!(reader, "{x},{y}")?;
+        Ok((x, y))
+    }
+
+    let mut reader = std::io::BufReader::new("1,2\nlast,line".as_bytes());
+    assert_eq!(read_pair(&mut reader).unwrap(), (1, 2));
+    let error = read_pair(&mut reader).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_scan_lines_tuples() {
+    let log = std::io::Cursor::new("10 INFO started\n11 WARN disk low\r\n");
+    let records: Vec<(u64, String, String)> = scan_lines!(log, "{ts} {level} {msg}")
+        .collect::<std::io::Result<_>>()
+        .unwrap();
+    assert_eq!(
+        records,
+        vec![
+            (10, "INFO".to_string(), "started".to_string()),
+            (11, "WARN".to_string(), "disk low".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_scan_lines_reports_line_number() {
+    let mut reader = std::io::Cursor::new("1\n2\nthree\n4\n");
+    let results: Vec<std::io::Result<u32>> = scan_lines!(&mut reader, "{}").collect();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[3].as_ref().unwrap(), &4);
+    let error = results[2].as_ref().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().starts_with("Line 3: "));
+}
+
+#[test]
+fn test_scan_lines_into_derived_structs() {
+    let reader = std::io::Cursor::new("(1,2)\n(3,4)");
+    let points: Vec<Point> = scan_lines!(reader, "{:Point}")
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(points, vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+}
+
+#[test]
+fn test_bscanf_parses_utf8_fields_of_binary_input() {
+    let input: &[u8] = b"\xff\xfe|temp=21.5|ok";
+    let mut header: &[u8] = &[];
+    let mut temp: f32 = 0.0;
+    let mut status: String = String::new();
+    bscanf!(input, b"{header:&[u8]}|temp={temp}|{status}").unwrap();
+    assert_eq!(header, b"\xff\xfe");
+    assert_eq!(temp, 21.5);
+    assert_eq!(status, "ok");
+}
+
+#[test]
+fn test_bscanf_rejects_invalid_utf8_field() {
+    let input = b"caf\xe9:42".to_vec();
+    let mut word: String = String::new();
+    let mut number: u32 = 0;
+    let error = bscanf!(&input, b"{}:{}", &mut word, &mut number).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(number, 42);
+}
+
+#[test]
+fn test_bscanf_fixed_width_counts_bytes() {
+    let input: &[u8] = b"\x01\x02AB0042";
+    let mut tag: &[u8] = &[];
+    let mut code: String = String::new();
+    let mut amount: u16 = 0;
+    bscanf!(input, b"{tag:&[u8]:2}{code:2}{amount}").unwrap();
+    assert_eq!(tag, [1, 2]);
+    assert_eq!(code, "AB");
+    assert_eq!(amount, 42);
+}
+
+#[test]
+fn test_borrowed_str_capture() {
+    let input = String::from("key=value;");
+    let mut key: &str = "";
+    let mut value: &str = "";
+    sscanf!(input.as_str(), "{key:&str}={value:&str};").unwrap();
+    assert_eq!((key, value), ("key", "value"));
+}
+
+#[test]
+fn test_char_class_fields() {
+    let input = "B7-abc,rest";
+    let mut letter: char = ' ';
+    let mut digit: u8 = 0;
+    let mut word: String = String::new();
+    let mut rest: String = String::new();
+    sscanf!(input, "{letter:[A-Z]}{digit:[0-9]}-{word:[^,]},{rest}").unwrap();
+    assert_eq!((letter, digit), ('B', 7));
+    assert_eq!(word, "abc");
+    assert_eq!(rest, "rest");
+}
+
+#[test]
+fn test_char_class_requires_one_match() {
+    let mut id: u32 = 0;
+    let mut tail: String = String::new();
+    let error = sscanf!("x12", "{id:[0-9]}{tail:[a-z]}").unwrap_err();
+    assert_eq!((id, tail.as_str()), (0, "x"));
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("[0-9]"));
+
+    let (id, tail): (u32, String) = sscan!("12x", "{:[0-9]}{:[a-z]}").unwrap();
+    assert_eq!(id, 12);
+    assert_eq!(tail, "x");
+}
+
+#[test]
+fn test_bscanf_char_class() {
+    let input: &[u8] = b"\xe9t\xe9=1";
+    let mut key: &[u8] = &[];
+    let mut value: u8 = 0;
+    bscanf!(input, b"{key:&[u8]:[^=]}={value}").unwrap();
+    assert_eq!(key, b"\xe9t\xe9");
+    assert_eq!(value, 1);
+}
+
+#[test]
+fn test_discard_placeholders() {
+    let input = "1234 sshd[42] 7: accepted";
+    let mut pid: u32 = 0;
+    let mut message: String = String::new();
+    sscanf!(input, "{_} {*}[{}] {_:i32}: {message}", &mut pid).unwrap();
+    assert_eq!(pid, 42);
+    assert_eq!(message, "accepted");
+
+    let (user, port): (String, u16) = sscan!("root x 22", "{} {_} {}").unwrap();
+    assert_eq!((user.as_str(), port), ("root", 22));
+}
+
+#[test]
+fn test_typed_discard_checks_field() {
+    let mut name: String = String::new();
+    let error = sscanf!("abc,bob", "{_:i32},{name}").unwrap_err();
+    assert!(error.to_string().contains("discarded placeholder #1"));
+    assert_eq!(name, "bob");
+}
+
+#[test]
+fn test_bscanf_discard_skips_utf8_check() {
+    let input: &[u8] = b"\xff\xfe:42";
+    let mut number: u32 = 0;
+    bscanf!(input, b"{_}:{number}").unwrap();
+    assert_eq!(number, 42);
+}
+
+#[test]
+fn test_radix_specifiers() {
+    let input = "ff 0x1F 17 101 -80";
+    let mut byte: u8 = 0;
+    let mut word: u32 = 0;
+    let mut octal: u16 = 0;
+    let mut bits: u64 = 0;
+    let mut signed: i8 = 0;
+    sscanf!(input, "{byte:x} {word:X} {octal:o} {bits:b} {signed:x}").unwrap();
+    assert_eq!(
+        (byte, word, octal, bits, signed),
+        (0xff, 0x1f, 0o17, 0b101, -128)
+    );
+
+    let (a, b): (u128, i64) =
+        sscan!("ffffffffffffffffffffffffffffffff,-0b11", "{:x},{:b}").unwrap();
+    assert_eq!((a, b), (u128::MAX, -3));
+
+    let (reg, value) = sscan!("R1=00ff", "R{:u8}={:u16:4x}").unwrap();
+    assert_eq!((reg, value), (1, 0xff));
+}
+
+#[test]
+fn test_radix_auto_detect() {
+    let (hex, octal, leading_zero, binary, decimal): (i32, u8, u8, u8, i64) =
+        sscan!("0x1F 0o17 017 0b11 -42", "{:i} {:i} {:i} {:i} {:i}").unwrap();
+    assert_eq!(
+        (hex, octal, leading_zero, binary, decimal),
+        (31, 15, 15, 3, -42)
+    );
+
+    let zero: u32 = sscan!("0", "{:i}").unwrap();
+    assert_eq!(zero, 0);
+}
+
+#[test]
+fn test_radix_prefix_and_range_errors() {
+    let value: u32 = sscan!("0x2a", "{:#x}").unwrap();
+    assert_eq!(value, 42);
+    assert!(sscan!("2a", "{:#x}").map(|value: u32| value).is_err());
+
+    let mut byte: u8 = 0;
+    let error = sscanf!("1ff", "{byte:x}").unwrap_err();
+    assert!(error.to_string().contains("too large"));
+    assert!(sscanf!("-1", "{byte:x}").is_err());
+    assert!(sscanf!("+-1", "{byte:x}").is_err());
+    assert!(sscanf!("0xg", "{byte:x}").is_err());
+    assert_eq!(byte, 0);
+}
+
+#[test]
+fn test_optional_section_present_and_absent() {
+    let mut user: String = String::new();
+    let mut uid: u32 = 0;
+    let mut shell: String = String::new();
+
+    sscanf!(
+        "user=bob [uid=1000] sh",
+        "user={user:[^ ]}[[ [uid={uid}]]]? {shell}"
+    )
+    .unwrap();
+    assert_eq!((user.as_str(), uid, shell.as_str()), ("bob", 1000, "sh"));
+
+    uid = 7;
+    sscanf!("user=amy zsh", "user={user:[^ ]}[[ [uid={uid}]]]? {shell}").unwrap();
+    assert_eq!((user.as_str(), uid, shell.as_str()), ("amy", 7, "zsh"));
+}
+
+#[test]
+fn test_optional_section_rolls_back_captures() {
+    let mut a: u32 = 1;
+    let mut b: u32 = 2;
+    let mut rest: String = String::new();
+    // `a` parses, but the section fails at `b`, so neither is assigned
+    sscanf!("x=5,y;tail", "[[x={a},{b};]]?{rest}").unwrap();
+    assert_eq!((a, b), (1, 2));
+    assert_eq!(rest, "x=5,y;tail");
+
+    let mut count: u32 = 0;
+    sscanf!("[3] items", "[[[{}] ]]?items", &mut count).unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_unpaired_section_markers_are_literal() {
+    let mut link: String = String::new();
+    sscanf!("[[Main]]", "[[{link}]]").unwrap();
+    assert_eq!(link, "Main");
+
+    let mut level: u8 = 0;
+    sscanf!("level 3]]?", "level {level}]]?").unwrap();
+    assert_eq!(level, 3);
+
+    let (page, anchor): (String, Option<String>) =
+        sscan!("[[Home#top", "[[{:[^#]}[[#{}]]?").unwrap();
+    assert_eq!(page, "Home");
+    assert_eq!(anchor.as_deref(), Some("top"));
+}
+
+#[test]
+fn test_optional_section_collects_options() {
+    let parse = |line: &str| -> std::io::Result<(String, Option<u16>)> {
+        sscan!(line, "{:[a-z.]}[[:{:u16}]]?")
+    };
+    assert_eq!(
+        parse("host:8080").unwrap(),
+        ("host".to_string(), Some(8080))
+    );
+    assert_eq!(parse("host").unwrap(), ("host".to_string(), None));
+    assert_eq!(parse("host:x").unwrap().1, None);
+
+    let nested: (Option<u8>, Option<u8>) = sscan!("1.2", "[[{:[0-9]}[[.{:[0-9]}]]?]]?").unwrap();
+    assert_eq!(nested, (Some(1), Some(2)));
+
+    sscanf_let!("v2", "v{major:u8:[0-9]}[[.{minor:u8:[0-9]}]]?" else {
+        panic!("invalid version");
+    });
+    assert_eq!((major, minor), (2, None));
+}
+
+#[test]
+fn test_derive_with_optional_section() {
+    #[derive(Scanf, Debug, PartialEq)]
+    #[scanf("{name:[a-z]}[[@{version}]]?")]
+    struct Package {
+        name: String,
+        version: Option<String>,
+    }
+
+    assert_eq!(
+        "serde@1.0".parse::<Package>().unwrap(),
+        Package {
+            name: "serde".to_string(),
+            version: Some("1.0".to_string())
+        }
+    );
+    assert_eq!("syn".parse::<Package>().unwrap().version, None);
+}
+
+#[test]
+fn test_repeated_capture_into_vec() {
+    let mut nums: Vec<u32> = Vec::new();
+    let mut label: String = String::new();
+    sscanf!("[1,2,3,4] total", "[{nums*,}] {label}").unwrap();
+    assert_eq!(nums, [1, 2, 3, 4]);
+    assert_eq!(label, "total");
+
+    sscanf!("[] none", "[{nums*,}] {label}").unwrap();
+    assert!(nums.is_empty());
+
+    let mut words: Vec<String> = Vec::new();
+    sscanf!("a :: b :: c", r#"{words;sep=" :: "}"#).unwrap();
+    assert_eq!(words, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_repeated_capture_typed_and_collected() {
+    let (nums, tail): (Vec<u8>, String) =
+        sscan!("ff,10,0a;end", r#"{:Vec<u8>:x;sep=","};{}"#).unwrap();
+    assert_eq!(nums, [0xff, 0x10, 0x0a]);
+    assert_eq!(tail, "end");
+
+    let fields: Vec<&str> = sscan!("a|b||c", "{:Vec<&str>;sep=\"|\"}").unwrap();
+    assert_eq!(fields, ["a", "b", "", "c"]);
+}
+
+#[test]
+fn test_repeated_capture_element_error() {
+    let mut nums: Vec<i32> = vec![9];
+    let error = sscanf!("1,x,3", "{nums*,}").unwrap_err();
+    assert!(error.to_string().contains("element of variable 'nums'"));
+    assert_eq!(nums, [9]);
+
+    let mut bytes: Vec<u8> = Vec::new();
+    bscanf!(b"1 2 3", b"{bytes* }").unwrap();
+    assert_eq!(bytes, [1, 2, 3]);
+}
+
+#[test]
+fn test_c_whitespace_mode() {
+    let mut a: i32 = 0;
+    let mut b: i32 = 0;
+    sscanf_ws!("10 20", "{}  {}", &mut a, &mut b).unwrap();
+    assert_eq!((a, b), (10, 20));
+    sscanf_ws!("\t30\n\n 40  ", "{} {}", &mut a, &mut b).unwrap();
+    assert_eq!((a, b), (30, 40));
+    sscanf_ws!("(5,6)", "( {a} , {b} )").unwrap();
+    assert_eq!((a, b), (5, 6));
+    sscanf_ws!("x=  7;y=8", "x={a};y={b}").unwrap();
+    assert_eq!((a, b), (7, 8));
+}
+
+#[test]
+fn test_c_whitespace_mode_fields() {
+    let mut name: String = String::new();
+    let mut tag: String = String::new();
+    let mut count: u32 = 0;
+    sscanf_ws!("  alice   [ x]  3", "{name} [{tag:[^]]}] {count}").unwrap();
+    assert_eq!(name, "alice");
+    assert_eq!(tag, " x");
+    assert_eq!(count, 3);
+
+    assert!(sscanf_ws!("1 2", "{count},{count}").is_err());
+}
+
+#[test]
+fn test_case_insensitive_literals() {
+    let mut host: String = String::new();
+    let mut port: u16 = 0;
+    sscanf!(
+        "HOST: Example.COM Port 8080",
+        "(?i)host: {host} port {port}"
+    )
+    .unwrap();
+    assert_eq!(host, "Example.COM");
+    assert_eq!(port, 8080);
+
+    assert!(sscanf!("HOST: a port 1", "host: {host} port {port}").is_err());
+
+    let (a, b): (String, String) = sscan!("Tea AnD Milk", "(?i){} and {}").unwrap();
+    assert_eq!((a.as_str(), b.as_str()), ("Tea", "Milk"));
+
+    let mut status: u16 = 0;
+    bscanf!(b"http/1.1 404", b"(?i)HTTP/1.1 {status}").unwrap();
+    assert_eq!(status, 404);
+
+    let items: Vec<String> = sscan!(
+        "tea AND milk and honey",
+        r#"(?i){:Vec<String>;sep=" and "}"#
+    )
+    .unwrap();
+    assert_eq!(items, ["tea", "milk", "honey"]);
+}
+
+#[test]
+fn test_case_insensitive_derive() {
+    #[derive(Scanf, Debug, PartialEq)]
+    enum Keyword {
+        #[scanf("(?i)select {column}")]
+        Select { column: String },
+        #[scanf("(?i)commit")]
+        Commit,
+    }
+
+    assert_eq!(
+        "SeLeCt Name".parse::<Keyword>().unwrap(),
+        Keyword::Select {
+            column: "Name".to_string()
+        }
+    );
+    assert_eq!("COMMIT".parse::<Keyword>().unwrap(), Keyword::Commit);
+}
+
+#[test]
+fn test_alternation_captures() {
+    let mut method: String = String::new();
+    let mut path: String = String::new();
+    sscanf!("POST /upload", "{method:(GET|POST)} {path}").unwrap();
+    assert_eq!(method, "POST");
+    assert_eq!(path, "/upload");
+
+    let error = sscanf!("PUT /upload", "{method:(GET|POST)} {path}").unwrap_err();
+    assert!(error.to_string().contains(r#""GET", "POST""#));
+
+    let (verb, target): (&str, String) = sscan!("get x", "(?i){:&str:(GET|POST)} {}").unwrap();
+    assert_eq!((verb, target.as_str()), ("get", "x"));
+}
+
+#[test]
+fn test_alternation_ends_previous_field() {
+    let mut name: String = String::new();
+    let mut amount: u32 = 0;
+    sscanf!("bob <= 10", "{name} {_:(<=|>=|<|>|=)} {amount}").unwrap();
+    assert_eq!((name.as_str(), amount), ("bob", 10));
+
+    let mut op: String = String::new();
+    let mut rest: String = String::new();
+    sscanf!("a+b-c", "{name}{op:(-|+)}{rest}").unwrap();
+    assert_eq!(
+        (name.as_str(), op.as_str(), rest.as_str()),
+        ("a", "+", "b-c")
+    );
+
+    let mut level: &[u8] = &[];
+    bscanf!(
+        b"\xff WARN: disk",
+        b"{_:&[u8]} {level:&[u8]:(INFO|WARN)}: {rest}"
+    )
+    .unwrap();
+    assert_eq!(level, b"WARN");
+}
+
+#[test]
+fn test_alternation_into_enum() {
+    #[derive(Debug, PartialEq)]
+    enum Method {
+        Get,
+        Post,
+    }
+
+    impl std::str::FromStr for Method {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "GET" => Ok(Method::Get),
+                "POST" => Ok(Method::Post),
+                _ => Err(format!("unknown method {s}")),
+            }
+        }
+    }
+
+    #[derive(Scanf, Debug, PartialEq)]
+    #[scanf("{method:(GET|POST)} {path}")]
+    struct Request {
+        method: Method,
+        path: String,
+    }
+
+    let request: Request = "GET /".parse().unwrap();
+    assert_eq!(
+        request,
+        Request {
+            method: Method::Get,
+            path: "/".to_string()
+        }
+    );
+}
+
+#[test]
+fn test_quoted_fields() {
+    let mut name: String = String::new();
+    let mut city: String = String::new();
+    let mut age: u32 = 0;
+    sscanf!(r#""Doe, John",'Rome, IT',42"#, "{name:q},{city:q},{age}").unwrap();
+    assert_eq!(name, "Doe, John");
+    assert_eq!(city, "Rome, IT");
+    assert_eq!(age, 42);
+
+    sscanf!(r#""say ""hi""","a\"b\\c\td""#, "{name:q},{city:q}").unwrap();
+    assert_eq!(name, r#"say "hi""#);
+    assert_eq!(city, "a\"b\\c\td");
+
+    let (count, word): (u8, String) = sscan!(r#""7"''"#, "{:q}{:q}").unwrap();
+    assert_eq!((count, word.as_str()), (7, ""));
+}
+
+#[test]
+fn test_quoted_field_errors() {
+    let mut text: String = String::new();
+    let error = sscanf!("plain", "{text:q}").unwrap_err();
+    assert!(error.to_string().contains("quoted string"));
+    assert!(sscanf!(r#""open"#, "{text:q}").is_err());
+    assert!(sscanf!(r#""a\""#, "{text:q}").is_err());
+    assert_eq!(text, "");
+}
+
+#[test]
+fn test_bscanf_quoted_field() {
+    let mut key: String = String::new();
+    let mut value: u16 = 0;
+    bscanf!(b"\xff 'the key' = 5", b"{_:&[u8]} {key:q} = {value}").unwrap();
+    assert_eq!((key.as_str(), value), ("the key", 5));
+}
+
+#[test]
+fn test_positional_placeholders() {
+    let mut day: u32 = 0;
+    let mut month: String = String::new();
+    sscanf!("March 14", "{1} {0}", &mut day, &mut month).unwrap();
+    assert_eq!((day, month.as_str()), (14, "March"));
+
+    let mut a: i32 = 0;
+    let mut b: i32 = 0;
+    sscanf!("5 6", "{1} {}", &mut a, &mut b).unwrap();
+    assert_eq!((a, b), (6, 5));
+    assert!(sscanf!("1-2", "{}-{0:i32}", &mut a).is_err());
+}
+
+#[test]
+fn test_positional_placeholder_repeated_must_match() {
+    let mut tag: String = String::new();
+    let mut body: String = String::new();
+    sscanf!("<b>bold</b>", "<{0}>{1}</{0}>", &mut tag, &mut body).unwrap();
+    assert_eq!((tag.as_str(), body.as_str()), ("b", "bold"));
+
+    let error = sscanf!("<b>bold</i>", "<{0}>{1}</{0}>", &mut tag, &mut body).unwrap_err();
+    assert!(error.to_string().contains("positional placeholder {0}"));
+    assert_eq!(body, "bold");
+
+    let mut n: u8 = 0;
+    sscanf!("7=07", "{}={0}", &mut n).unwrap();
+    assert_eq!(n, 7);
+}
+
+#[test]
+fn test_field_path_placeholders() {
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+    #[derive(Default)]
+    struct Shape {
+        origin: Point,
+        sides: [u8; 3],
+        label: (String, u32),
+    }
+
+    let mut shape = Shape::default();
+    sscanf!(
+        "(3,-4) 5/6/7 tri#2",
+        "({shape.origin.x},{shape.origin.y}) {shape.sides[0]}/{shape.sides[1]}/{shape.sides[2]} \
+         {shape.label.0}#{shape.label.1}"
+    )
+    .unwrap();
+    assert_eq!((shape.origin.x, shape.origin.y), (3, -4));
+    assert_eq!(shape.sides, [5, 6, 7]);
+    assert_eq!(shape.label, ("tri".to_string(), 2));
+
+    let mut points: Vec<Point> = (0..2).map(|_| Point::default()).collect();
+    sscanf!("9 8", "{points[1].x} {points[0].y}").unwrap();
+    assert_eq!((points[1].x, points[0].y), (9, 8));
+}
+
+#[test]
+fn test_field_path_on_self() {
+    #[derive(Default)]
+    struct Config {
+        name: String,
+        port: u16,
+    }
+
+    impl Config {
+        fn load(&mut self, line: &str) -> std::io::Result<()> {
+            sscanf!(line, "{self.name}:{self.port:u16}")
+        }
+    }
+
+    let mut config = Config::default();
+    config.load("db:5432").unwrap();
+    assert_eq!((config.name.as_str(), config.port), ("db", 5432));
+}
+
+#[test]
+fn test_named_arguments() {
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    let mut server = Server {
+        host: String::new(),
+        port: 0,
+    };
+    sscanf!(
+        "example.org:8080",
+        "{host}:{port}",
+        host = &mut server.host,
+        port = &mut server.port
+    )
+    .unwrap();
+    assert_eq!((server.host.as_str(), server.port), ("example.org", 8080));
+
+    let mut scheme: String = String::new();
+    let mut path: String = String::new();
+    sscanf!(
+        "https://x/docs",
+        "{}://{host}/{path}",
+        &mut scheme,
+        host = &mut server.host
+    )
+    .unwrap();
+    assert_eq!((scheme.as_str(), server.host.as_str()), ("https", "x"));
+    assert_eq!(path, "docs");
+}
+
+#[test]
+fn test_named_argument_repeated_must_match() {
+    let mut word: String = String::new();
+    sscanf!("ab-ab", "{w}-{w}", w = &mut word).unwrap();
+    assert_eq!(word, "ab");
+    assert!(sscanf!("ab-cd", "{w}-{w}", w = &mut word).is_err());
+
+    let mut reader = std::io::Cursor::new("7 7\n");
+    let mut n: u32 = 0;
+    fscanf!(&mut reader, "{n} {n}", n = &mut n).unwrap();
+    assert_eq!(n, 7);
+}
+
+#[test]
+fn test_backtracking_retries_later_separators() {
+    let mut name: String = String::new();
+    let mut port: i32 = 0;
+    assert!(sscanf!("a:b:3", "{name}:{port}").is_err());
+    sscanf!("a:b:3", "(?b){name}:{port}").unwrap();
+    assert_eq!((name.as_str(), port), ("a:b", 3));
+
+    let (key, value, unit): (String, f64, String) = sscan!("x=y=1.5 kg", "(?b){}={} {}").unwrap();
+    assert_eq!((key.as_str(), value, unit.as_str()), ("x=y", 1.5, "kg"));
+
+    let mut flag: bool = false;
+    bscanf!(b"a,b,true,c", b"(?b){name},{flag},{_}").unwrap();
+    assert_eq!((name.as_str(), flag), ("a,b", true));
+
+    let error = sscanf!("a:b", "(?b){name}:{port}").unwrap_err();
+    assert!(error.to_string().contains("port"));
+}
+
+#[test]
+fn test_backtracking_with_whole_input_and_flags() {
+    #[derive(Scanf, Debug, PartialEq)]
+    #[scanf("(?bi){path} LINE {line}!")]
+    struct Location {
+        path: String,
+        line: u32,
+    }
+
+    let location: Location = "a line b.rs line 12!".parse().unwrap();
+    assert_eq!(
+        location,
+        Location {
+            path: "a line b.rs".to_string(),
+            line: 12
+        }
+    );
+
+    let found = scanf_match!("1!2!3!", {
+        "(?b){a:String}!{b:u8}!" => (a, b),
+        _ => (String::new(), 0),
+    });
+    assert_eq!(found, ("1!2".to_string(), 3));
+}
+
+#[test]
+fn test_backtracking_step_budget() {
+    let input = ",".repeat(200);
+    let result: std::io::Result<(String, String, String, u8)> =
+        sscan!(input.as_str(), "(?b){},{},{},{}");
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("Gave up backtracking"));
+}
+
+#[test]
+fn test_backtracking_skips_optional_sections() {
+    let mut a: String = String::new();
+    let mut b: u8 = 0;
+    let mut c: String = String::new();
+    let mut d: String = String::new();
+    let mut e: String = String::new();
+    let input = format!("p:q:1 {}", ",".repeat(200));
+    sscanf!(input.as_str(), "(?b){a}:{b} [[{c},{d},{e}x]]?").unwrap();
+    assert_eq!((a.as_str(), b), ("p:q", 1));
+    assert!(c.is_empty() && d.is_empty() && e.is_empty());
+}
+
+#[test]
+fn test_backtracking_work_is_bounded_by_input_size() {
+    use std::cell::Cell;
+
+    thread_local! {
+        static PARSED_BYTES: Cell<usize> = const { Cell::new(0) };
+        static FORMATTED_ERRORS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // Counts the bytes it parses; fails on any non-digit
+    #[derive(Debug)]
+    struct Counted;
+    #[derive(Debug)]
+    struct NotDigits;
+    impl std::fmt::Display for NotDigits {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            FORMATTED_ERRORS.with(|count| count.set(count.get() + 1));
+            f.write_str("not digits")
+        }
+    }
+    impl std::error::Error for NotDigits {}
+    impl std::str::FromStr for Counted {
+        type Err = NotDigits;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            PARSED_BYTES.with(|bytes| bytes.set(bytes.get() + s.len()));
+            if s.bytes().all(|b| b.is_ascii_digit()) {
+                Ok(Counted)
+            } else {
+                Err(NotDigits)
+            }
+        }
+    }
+
+    for tail in [16 << 10, 1 << 20] {
+        PARSED_BYTES.with(|bytes| bytes.set(0));
+        FORMATTED_ERRORS.with(|count| count.set(0));
+        let input = ",".repeat(100) + &"a".repeat(tail);
+        let result: std::io::Result<(String, String, String, Counted)> =
+            sscan!(input.as_str(), "(?b){},{},{},{}");
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Gave up backtracking")
+        );
+        // Retries are charged the input length, within a budget of 1 MiB
+        let parsed = PARSED_BYTES.with(Cell::get);
+        assert!(
+            parsed <= (1 << 20) + 2 * input.len(),
+            "parsed {} bytes",
+            parsed
+        );
+        // Only the reported attempt formats its error message
+        assert_eq!(FORMATTED_ERRORS.with(Cell::get), 1);
+    }
+}
+
+#[test]
+fn test_last_separator_fields() {
+    let mut path: String = String::new();
+    let mut port: u16 = 0;
+    sscanf!("/a:b/c:42", "{path:last}:{port}").unwrap();
+    assert_eq!((path.as_str(), port), ("/a:b/c", 42));
+
+    let mut unit: String = String::new();
+    sscanf!("a:b:7 ms x:9 ms", "{path:last}:{port} ms {unit}").unwrap();
+    assert_eq!((path.as_str(), port, unit.as_str()), ("a:b", 7, "x:9 ms"));
+
+    let (file, line): (String, u32) = sscan!("C:\\src\\lib.rs:12", "{:last}:{}").unwrap();
+    assert_eq!((file.as_str(), line), ("C:\\src\\lib.rs", 12));
+
+    let mut name: &[u8] = &[];
+    bscanf!(b"a.tar.gz", b"{name:&[u8]:last}.{unit}").unwrap();
+    assert_eq!((name, unit.as_str()), (&b"a.tar"[..], "gz"));
+
+    assert!(sscanf!("no separator", "{path:last}:{port}").is_err());
+}
+
+#[test]
+fn test_last_separator_case_and_whitespace_modes() {
+    let mut key: String = String::new();
+    let mut value: String = String::new();
+    sscanf!("a AND b and c", "(?i){key:last} and {value}").unwrap();
+    assert_eq!((key.as_str(), value.as_str()), ("a AND b", "c"));
+
+    let mut port: u16 = 0;
+    sscanf_ws!("  host:a:80  ;", "{key:last}:{port} ;").unwrap();
+    assert_eq!((key.as_str(), port), ("host:a", 80));
+}
+
 // ============================================================================
 // Security Tests
 // ============================================================================
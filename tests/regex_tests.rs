@@ -0,0 +1,71 @@
+#![cfg(feature = "regex")]
+
+use scanf::{bscanf, sscan, sscanf};
+
+#[test]
+fn test_regex_placeholder_delimits_field() {
+    let input = "nginx/1.25.3 (ubuntu) up";
+    let mut name: String = String::new();
+    let mut version: String = String::new();
+    let mut os: String = String::new();
+    sscanf!(input, r"{name}/{version:/\d+(\.\d+)*/} ({os}) up").unwrap();
+    assert_eq!(name, "nginx");
+    assert_eq!(version, "1.25.3");
+    assert_eq!(os, "ubuntu");
+}
+
+#[test]
+fn test_regex_is_anchored_and_parsed() {
+    let (host, port): (String, u16) =
+        sscan!("db-1.local:5432", r"{:/[a-z0-9.-]+/}:{:u16:/\d{1,5}/}").unwrap();
+    assert_eq!(host, "db-1.local");
+    assert_eq!(port, 5432);
+
+    let mut port: u16 = 0;
+    let error = sscanf!("x80", r"{port:/\d+/}").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(error.to_string().contains("regex"));
+    assert_eq!(port, 0);
+}
+
+#[test]
+fn test_regex_escaped_slash() {
+    let (path, rest): (String, String) = sscan!("/usr/lib rest", r"{:/[\/a-z]+/} {}").unwrap();
+    assert_eq!(path, "/usr/lib");
+    assert_eq!(rest, "rest");
+}
+
+#[test]
+fn test_bscanf_regex() {
+    let input: &[u8] = b"\xff\xfeABC;1";
+    let mut magic: &[u8] = &[];
+    let mut code: String = String::new();
+    let mut value: u8 = 0;
+    bscanf!(
+        input,
+        br"{magic:&[u8]:/(?-u:\xff\xfe)/}{code:/[A-Z]+/};{value}"
+    )
+    .unwrap();
+    assert_eq!(magic, b"\xff\xfe");
+    assert_eq!(code, "ABC");
+    assert_eq!(value, 1);
+}
+
+#[test]
+fn test_regex_verbose_mode_with_trailing_comment() {
+    let mut v: String = String::new();
+    sscanf!("abc!", r"{v:/(?x)abc # c/}!").unwrap();
+    assert_eq!(v, "abc");
+
+    let mut word: &[u8] = &[];
+    bscanf!(
+        b"a b!",
+        br"{word:&[u8]:/(?x) a \  b # spaces are escaped/}!"
+    )
+    .unwrap();
+    assert_eq!(word, b"a b");
+
+    // Outside x mode the pattern is unchanged, so no newline is required after it
+    let (text, rest): (String, String) = sscan!("ab#c", r"{:/ab#/}{}").unwrap();
+    assert_eq!((text.as_str(), rest.as_str()), ("ab#", "c"));
+}